@@ -0,0 +1,94 @@
+use std::{path::Path, process::Command};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use sea_orm::{ConnectionTrait, Database};
+
+const PROJECTS: usize = 10;
+const TASKS_PER_PROJECT: usize = 10;
+const TIME_ENTRIES: usize = 100_000;
+
+fn run_clog(args: &[&str], db_path: &Path) {
+    let output = Command::new(env!("CARGO_BIN_EXE_clog"))
+        .args(args)
+        .env("CLOG_DATABASE_PATH", db_path)
+        // The config file of the developer could change the output or sync the database,
+        // so a file which doesn't exist is used for the defaults
+        .env("CLOG_CONFIG", db_path.with_extension("toml"))
+        .env_remove("CLOG_PROFILE")
+        .output()
+        .expect("Failed to execute clog");
+    assert!(
+        output.status.success(),
+        "clog {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Create a database with the projects and tasks and spread the time entries
+/// over the tasks, one entry every two hours going back from the end of 2024
+fn seed(db_path: &Path) {
+    if db_path.exists() {
+        std::fs::remove_file(db_path).expect("Failed to remove benchmark database");
+    }
+    // Let clog create the database and run the migrations
+    run_clog(&["project", "list"], db_path);
+
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+    runtime.block_on(async {
+        let db = Database::connect(format!("sqlite://{}?mode=rwc", db_path.display()))
+            .await
+            .expect("Failed to connect to benchmark database");
+        let statements = [
+            format!(
+                "WITH RECURSIVE seq(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM seq WHERE n + 1 < {PROJECTS})
+                 INSERT INTO projects (name, description) SELECT 'project_' || n, 'Benchmark project' FROM seq"
+            ),
+            format!(
+                "WITH RECURSIVE seq(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM seq WHERE n + 1 < {TASKS_PER_PROJECT})
+                 INSERT INTO tasks (project_id, name, status)
+                 SELECT projects.id, 'task_' || seq.n, 'in_progress' FROM projects, seq"
+            ),
+            format!(
+                "WITH RECURSIVE seq(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM seq WHERE n + 1 < {TIME_ENTRIES})
+                 INSERT INTO time_entries (task_id, start_time, end_time, duration)
+                 SELECT
+                     n % (SELECT COUNT(*) FROM tasks) + 1,
+                     datetime('2024-12-31 08:00:00', '-' || (n * 2) || ' hours'),
+                     datetime('2024-12-31 08:00:00', '-' || (n * 2) || ' hours', '+' || (n % 90 + 15) || ' minutes'),
+                     (n % 90 + 15) * 60
+                 FROM seq"
+            ),
+        ];
+        for statement in statements {
+            db.execute_unprepared(&statement)
+                .await
+                .expect("Failed to seed benchmark database");
+        }
+    });
+}
+
+fn bench_reports(c: &mut Criterion) {
+    let db_path = std::env::temp_dir().join("clog_bench_report.sqlite");
+    seed(&db_path);
+
+    let mut group = c.benchmark_group("100k_time_entries");
+    group.sample_size(10);
+    let commands: [(&str, &[&str]); 5] = [
+        ("report_project", &["report", "project"]),
+        (
+            "report_project_week",
+            &["report", "project", "-w", "2024-W30"],
+        ),
+        ("report_task", &["report", "task", "-p", "project_0"]),
+        ("project_list", &["project", "list"]),
+        ("task_list", &["task", "list", "-p", "project_0"]),
+    ];
+    for (name, args) in commands {
+        group.bench_function(name, |b| b.iter(|| run_clog(args, &db_path)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_reports);
+criterion_main!(benches);
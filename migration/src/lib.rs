@@ -1,12 +1,20 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20261018_000001_add_time_entry_indexes;
+mod m20261018_000002_add_sync_columns;
+mod m20261019_000001_add_clients_and_tags;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20261018_000001_add_time_entry_indexes::Migration),
+            Box::new(m20261018_000002_add_sync_columns::Migration),
+            Box::new(m20261019_000001_add_clients_and_tags::Migration),
+        ]
     }
 }
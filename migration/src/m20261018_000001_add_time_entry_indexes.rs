@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const TASK_ID_INDEX: &str = "idx_time_entries_task_id";
+const START_TIME_INDEX: &str = "idx_time_entries_start_time";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Index the foreign key used to join the time entries with their tasks
+        manager
+            .create_index(
+                Index::create()
+                    .name(TASK_ID_INDEX)
+                    .table(TimeEntries::Table)
+                    .col(TimeEntries::TaskId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index the start time used to filter the time entries of a report
+        manager
+            .create_index(
+                Index::create()
+                    .name(START_TIME_INDEX)
+                    .table(TimeEntries::Table)
+                    .col(TimeEntries::StartTime)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(START_TIME_INDEX)
+                    .table(TimeEntries::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(TASK_ID_INDEX)
+                    .table(TimeEntries::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TimeEntries {
+    Table,
+    TaskId,
+    StartTime,
+}
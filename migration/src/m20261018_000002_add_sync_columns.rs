@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables which are merged row by row when syncing
+const SYNCED_TABLES: [&str; 3] = ["projects", "tasks", "time_entries"];
+
+/// Random UUID v4 generated by SQLite
+const UUID_V4_SQL: &str = "lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' \
+    || substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) \
+    || substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)))";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for table in SYNCED_TABLES {
+            // Stable identifier of the row across databases, the ids are only unique per database
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .add_column(
+                            ColumnDef::new(Alias::new("uuid"))
+                                .string_len(36)
+                                .not_null()
+                                .default(""),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+            db.execute_unprepared(&format!("UPDATE {table} SET uuid = {UUID_V4_SQL}"))
+                .await?;
+            manager
+                .create_index(
+                    Index::create()
+                        .name(format!("idx_{table}_uuid"))
+                        .table(Alias::new(table))
+                        .col(Alias::new("uuid"))
+                        .unique()
+                        .to_owned(),
+                )
+                .await?;
+
+            // Rows inserted without a UUID get a random one
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER {table}_generate_uuid AFTER INSERT ON {table} \
+                 FOR EACH ROW WHEN NEW.uuid = '' \
+                 BEGIN UPDATE {table} SET uuid = {UUID_V4_SQL} WHERE id = NEW.id; END"
+            ))
+            .await?;
+            // Track changes by updating the modification time of every row which is updated
+            // without setting it explicitly
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER {table}_touch_updated_at AFTER UPDATE ON {table} \
+                 FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at \
+                 BEGIN UPDATE {table} SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id; END"
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for table in SYNCED_TABLES.into_iter().rev() {
+            db.execute_unprepared(&format!("DROP TRIGGER IF EXISTS {table}_touch_updated_at"))
+                .await?;
+            db.execute_unprepared(&format!("DROP TRIGGER IF EXISTS {table}_generate_uuid"))
+                .await?;
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name(format!("idx_{table}_uuid"))
+                        .table(Alias::new(table))
+                        .to_owned(),
+                )
+                .await?;
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .drop_column(Alias::new("uuid"))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, Utc};
+use sea_orm::DatabaseConnection;
+
+use crate::{config::BackupConfig, db};
+
+const BACKUP_DIR_SUFFIX: &str = ".backups";
+const BACKUP_FILE_EXTENSION: &str = "sqlite";
+/// Timestamp at the start of a backup file name, sortable and without characters
+/// which are invalid in file names
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// Reason why a backup of the local database was created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BackupReason {
+    Manual,
+    Pull,
+    Merge,
+    Restore,
+    Migration,
+}
+
+impl BackupReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BackupReason::Manual => "manual",
+            BackupReason::Pull => "pull",
+            BackupReason::Merge => "merge",
+            BackupReason::Restore => "restore",
+            BackupReason::Migration => "migration",
+        }
+    }
+}
+
+/// Timestamped copy of the local database, stored as
+/// `<database>.backups/<timestamp>-<reason>.sqlite` next to the database
+#[derive(Debug, Clone)]
+pub(crate) struct Backup {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) reason: String,
+    pub(crate) size: u64,
+}
+
+impl Backup {
+    fn from_path(path: PathBuf, size: u64) -> Option<Self> {
+        if path.extension()? != BACKUP_FILE_EXTENSION {
+            return None;
+        }
+        let name = path.file_stem()?.to_str()?.to_string();
+        let (timestamp, reason) = name.split_once('-')?;
+        let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+            .ok()?
+            .and_utc();
+        Some(Self {
+            reason: reason.to_string(),
+            name,
+            path,
+            created_at,
+            size,
+        })
+    }
+}
+
+pub(crate) fn backup_dir(db_path: &Path) -> PathBuf {
+    db::sidecar_path(db_path, BACKUP_DIR_SUFFIX)
+}
+
+/// Back up the database and apply the retention policy to the existing backups
+pub(crate) async fn create(
+    db: &DatabaseConnection,
+    db_path: &Path,
+    reason: BackupReason,
+    config: &BackupConfig,
+) -> miette::Result<Backup> {
+    let backup_dir = backup_dir(db_path);
+    tokio::fs::create_dir_all(&backup_dir).await.map_err(|e| {
+        miette::miette!(
+            "Failed to create backup directory {}: {}",
+            backup_dir.display(),
+            e
+        )
+    })?;
+
+    let created_at = Utc::now();
+    let name = format!(
+        "{}-{}",
+        created_at.format(TIMESTAMP_FORMAT),
+        reason.as_str()
+    );
+    let path = backup_dir.join(format!("{name}.{BACKUP_FILE_EXTENSION}"));
+    db::vacuum_into(db, &path).await?;
+    let size = file_size(&path).await?;
+
+    // The new backup is the newest one and therefore always kept
+    prune(db_path, config).await?;
+    Backup::from_path(path, size)
+        .ok_or_else(|| miette::miette!("Failed to read backup name {}", name))
+}
+
+/// All backups of the database, the newest first
+pub(crate) async fn list(db_path: &Path) -> miette::Result<Vec<Backup>> {
+    let backup_dir = backup_dir(db_path);
+    let mut entries = match tokio::fs::read_dir(&backup_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(miette::miette!(
+                "Failed to read backup directory {}: {}",
+                backup_dir.display(),
+                e
+            ));
+        }
+    };
+
+    let mut backups = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| miette::miette!("Failed to read backup directory entry: {}", e))?
+    {
+        let path = entry.path();
+        let size = file_size(&path).await?;
+        // Ignore files which were not created by clog
+        if let Some(backup) = Backup::from_path(path, size) {
+            backups.push(backup);
+        }
+    }
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// Backup with the given name, or the newest backup
+pub(crate) async fn find(db_path: &Path, name: Option<&str>) -> miette::Result<Backup> {
+    let backups = list(db_path).await?;
+    match name {
+        Some(name) => backups
+            .into_iter()
+            .find(|backup| backup.name == name)
+            .ok_or_else(|| {
+                miette::miette!(
+                    help = "Use `clog backup list` to show the available backups",
+                    "Backup '{}' not found",
+                    name
+                )
+            }),
+        None => backups.into_iter().next().ok_or_else(|| {
+            miette::miette!(
+                help = "Use `clog backup create` to create one",
+                "There are no backups of the database"
+            )
+        }),
+    }
+}
+
+/// Delete the backups which are not kept by the retention policy, the newest backup is
+/// always kept
+async fn prune(db_path: &Path, config: &BackupConfig) -> miette::Result<()> {
+    let backups = list(db_path).await?;
+    let mut kept_days = HashSet::new();
+    let mut kept_weeks = HashSet::new();
+    for (index, backup) in backups.iter().enumerate() {
+        let created_at = backup.created_at.with_timezone(&Local).date_naive();
+        // The newest backup of a local day or week is kept for it
+        let keep_daily = kept_days.len() < config.keep_daily && kept_days.insert(created_at);
+        let keep_weekly =
+            kept_weeks.len() < config.keep_weekly && kept_weeks.insert(created_at.iso_week());
+        if index == 0 || index < config.keep_last || keep_daily || keep_weekly {
+            continue;
+        }
+        tokio::fs::remove_file(&backup.path).await.map_err(|e| {
+            miette::miette!("Failed to remove backup {}: {}", backup.path.display(), e)
+        })?;
+    }
+    Ok(())
+}
+
+async fn file_size(path: &Path) -> miette::Result<u64> {
+    tokio::fs::metadata(path)
+        .await
+        .map(|metadata| metadata.len())
+        .map_err(|e| miette::miette!("Failed to read metadata of {}: {}", path.display(), e))
+}
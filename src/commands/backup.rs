@@ -0,0 +1,140 @@
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+use super::CommandExecutorTrait;
+use crate::{
+    Context,
+    backup::{self, BackupReason},
+    commands::{
+        OutputOptions,
+        command_output::{CommandOutput, NoTable, format_time},
+    },
+    config::Config,
+    db,
+};
+
+#[derive(Subcommand)]
+pub(super) enum BackupCommand {
+    /// List the backups of the local database, the newest first
+    List(ListBackupsCommand),
+    /// Create a backup of the local database
+    Create(CreateBackupCommand),
+    /// Replace the local database with a backup
+    Restore(RestoreBackupCommand),
+}
+
+#[derive(Parser)]
+pub(super) struct ListBackupsCommand;
+
+#[derive(Parser)]
+pub(super) struct CreateBackupCommand;
+
+#[derive(Parser)]
+pub(super) struct RestoreBackupCommand {
+    /// Name of the backup to restore, defaults to the newest backup
+    #[clap(short, long)]
+    name: Option<String>,
+}
+
+impl CommandExecutorTrait for BackupCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        match self {
+            BackupCommand::List(_) => list(&ctx.config, output).await,
+            BackupCommand::Create(_) => create(&ctx, output).await,
+            BackupCommand::Restore(cmd) => restore(ctx, cmd.name.as_deref(), output).await,
+        }
+    }
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct BackupTable {
+    name: String,
+    created: String,
+    reason: String,
+    size: u64,
+}
+
+async fn list(config: &Config, output: &OutputOptions) -> miette::Result<()> {
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let backups_table = backup::list(&db_path)
+        .await?
+        .into_iter()
+        .map(|backup| BackupTable {
+            name: backup.name,
+            created: format_time(backup.created_at),
+            reason: backup.reason,
+            size: backup.size,
+        })
+        .collect::<Vec<_>>();
+
+    CommandOutput::<Vec<BackupTable>, BackupTable>::builder()
+        .with_table_rows(backups_table)
+        .with_options(output)
+        .with_prefix_message("All backups of the database".to_string())
+        .build()
+        .print()?;
+    Ok(())
+}
+
+async fn create(ctx: &Context, output: &OutputOptions) -> miette::Result<()> {
+    let db_path = db::db_path(&ctx.config)
+        .map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let created_backup =
+        backup::create(&ctx.db, &db_path, BackupReason::Manual, &ctx.config.backup).await?;
+
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(format!(
+            "Backup created successfully at {}",
+            created_backup.path.display()
+        ))
+        .build()
+        .print()?;
+    Ok(())
+}
+
+async fn restore(ctx: Context, name: Option<&str>, output: &OutputOptions) -> miette::Result<()> {
+    let db_path = db::db_path(&ctx.config)
+        .map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let restored_backup = backup::find(&db_path, name).await?;
+
+    // Validate a copy of the backup next to the local database, so it can be renamed over it
+    let restore_path = db::sidecar_path(&db_path, ".restore.sqlite");
+    tokio::fs::copy(&restored_backup.path, &restore_path)
+        .await
+        .map_err(|e| {
+            miette::miette!(
+                "Failed to copy backup {} to {}: {}",
+                restored_backup.path.display(),
+                restore_path.display(),
+                e
+            )
+        })?;
+    let restored_db = match db::open_validated(&restore_path).await {
+        Ok(restored_db) => restored_db,
+        Err(e) => {
+            db::remove_if_exists(&restore_path).await?;
+            return Err(e);
+        }
+    };
+    restored_db
+        .close()
+        .await
+        .map_err(|e| miette::miette!("Failed to close restored database: {}", e))?;
+
+    // The current database can be restored in turn if the backup was the wrong one
+    backup::create(&ctx.db, &db_path, BackupReason::Restore, &ctx.config.backup).await?;
+    db::replace(ctx.db, &db_path, &restore_path).await?;
+
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(format!(
+            "Backup '{}' restored successfully",
+            restored_backup.name
+        ))
+        .build()
+        .print()?;
+    Ok(())
+}
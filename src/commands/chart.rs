@@ -0,0 +1,274 @@
+use std::io::IsTerminal;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::duration::DurationFormat;
+
+const BAR_WIDTH: usize = 40;
+/// Foreground colors for the heatmap levels, from no tracked time to the most tracked time
+const HEATMAP_COLORS: [u8; 5] = [238, 22, 28, 34, 40];
+const BAR_COLOR: u8 = 34;
+
+/// Characters and colors used to draw charts in the terminal
+pub(super) struct ChartStyle {
+    unicode: bool,
+    color: bool,
+}
+
+impl ChartStyle {
+    /// Detect the capabilities of the terminal, `ascii` forces plain ASCII characters.
+    /// Colors are disabled when `NO_COLOR` is set or stdout is not a terminal.
+    pub(super) fn detect(ascii: bool) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let utf8_locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .is_some_and(|locale| {
+                let locale = locale.to_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            });
+        Self {
+            unicode: !ascii && utf8_locale,
+            color: !no_color && std::io::stdout().is_terminal(),
+        }
+    }
+
+    fn heatmap_glyphs(&self) -> [&'static str; 5] {
+        if self.unicode {
+            ["·", "░", "▒", "▓", "█"]
+        } else {
+            [".", "-", "+", "*", "#"]
+        }
+    }
+
+    fn paint(&self, text: &str, color: u8) -> String {
+        if self.color {
+            format!("\x1b[38;5;{color}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bar(&self, value: i64, max: i64) -> String {
+        if max <= 0 || value <= 0 {
+            return String::new();
+        }
+        // Width of the bar in eighths of a character
+        let eighths = (value as f64 / max as f64 * (BAR_WIDTH * 8) as f64).round() as usize;
+        let bar = if self.unicode {
+            const PARTIAL: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+            format!("{}{}", "█".repeat(eighths / 8), PARTIAL[eighths % 8])
+        } else {
+            "#".repeat((eighths / 8).max(1))
+        };
+        self.paint(&bar, BAR_COLOR)
+    }
+}
+
+/// Render a GitHub style heatmap with one column per week and one row per weekday
+pub(super) fn heatmap(
+    style: &ChartStyle,
+    from: NaiveDate,
+    to: NaiveDate,
+    secs_for_day: impl Fn(NaiveDate) -> i64,
+) -> Vec<String> {
+    let first_monday = from - chrono::Days::new(u64::from(from.weekday().num_days_from_monday()));
+    let weeks = (to - first_monday).num_days() / 7 + 1;
+    let max = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .map(&secs_for_day)
+        .max()
+        .unwrap_or(0);
+    let glyphs = style.heatmap_glyphs();
+
+    // Month labels above the first week of every month
+    let mut month_line = vec![' '; 4 + weeks as usize * 2];
+    let mut last_month = None;
+    let mut label_end = 0;
+    for week in 0..weeks as usize {
+        let monday = first_monday + chrono::Days::new(week as u64 * 7);
+        let position = 4 + week * 2;
+        let label = monday.format("%b").to_string();
+        if last_month != Some(monday.month())
+            && position >= label_end
+            && position + label.len() <= month_line.len()
+        {
+            month_line.splice(position..position + label.len(), label.chars());
+            label_end = position + label.len() + 1;
+            last_month = Some(monday.month());
+        }
+    }
+    let month_line = month_line.into_iter().collect::<String>();
+
+    let mut lines = vec![month_line.trim_end().to_string()];
+    for weekday in 0..7 {
+        let mut line = format!(
+            "{} ",
+            (first_monday + chrono::Days::new(weekday)).format("%a")
+        );
+        for week in 0..weeks {
+            let day = first_monday + chrono::Days::new(week as u64 * 7 + weekday);
+            if day < from || day > to {
+                line.push_str("  ");
+                continue;
+            }
+            let level = heatmap_level(secs_for_day(day), max);
+            line.push_str(&style.paint(glyphs[level], HEATMAP_COLORS[level]));
+            line.push(' ');
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    let legend = glyphs
+        .iter()
+        .zip(HEATMAP_COLORS)
+        .map(|(glyph, color)| style.paint(glyph, color))
+        .collect::<Vec<_>>()
+        .join(" ");
+    lines.push(format!("    Less {legend} More"));
+    lines
+}
+
+fn heatmap_level(secs: i64, max: i64) -> usize {
+    if secs <= 0 || max <= 0 {
+        return 0;
+    }
+    ((secs as f64 / max as f64 * 4.0).ceil() as usize).clamp(1, 4)
+}
+
+/// Render horizontal bars for the labelled values, sorted by the largest value
+pub(super) fn bar_chart(
+    style: &ChartStyle,
+    duration_format: DurationFormat,
+    values: &[(String, i64)],
+) -> Vec<String> {
+    let mut values = values.to_vec();
+    values.sort_by(|(a_label, a), (b_label, b)| b.cmp(a).then_with(|| a_label.cmp(b_label)));
+    let max = values.first().map(|(_, secs)| *secs).unwrap_or(0);
+    let label_width = values
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    values
+        .iter()
+        .map(|(label, secs)| {
+            let bar = style.bar(*secs, max);
+            let padding = BAR_WIDTH + 1 - visible_width(&bar).min(BAR_WIDTH);
+            format!(
+                "{label:<label_width$} {bar}{}{}",
+                " ".repeat(padding),
+                duration_format.format(*secs)
+            )
+        })
+        .collect()
+}
+
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip ANSI escape sequences
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASCII: ChartStyle = ChartStyle {
+        unicode: false,
+        color: false,
+    };
+    const UNICODE: ChartStyle = ChartStyle {
+        unicode: true,
+        color: false,
+    };
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    /// Glyphs of the days in the rows of a heatmap, without the weekday labels
+    fn heatmap_days(lines: &[String]) -> Vec<String> {
+        lines[1..8]
+            .iter()
+            .map(|line| line[3..].replace(' ', ""))
+            .collect()
+    }
+
+    #[test]
+    fn ascii_fallback() {
+        assert!(!ChartStyle::detect(true).unicode);
+        assert_eq!(ASCII.heatmap_glyphs(), [".", "-", "+", "*", "#"]);
+        assert_eq!(ASCII.bar(1, 320), "#");
+        assert_eq!(UNICODE.bar(1, 320), "▏");
+    }
+
+    #[test]
+    fn heatmap_without_tracked_time() {
+        // Monday 2025-02-03 to Sunday 2025-02-16
+        let lines = heatmap(&ASCII, date("2025-02-03"), date("2025-02-16"), |_| 0);
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0], "    Feb");
+        assert_eq!(heatmap_days(&lines), vec![".."; 7]);
+        assert_eq!(lines[8], "    Less . - + * # More");
+    }
+
+    #[test]
+    fn heatmap_single_day() {
+        let day = date("2025-02-05");
+        let lines = heatmap(&ASCII, day, day, |_| 3600);
+        assert_eq!(heatmap_days(&lines), ["", "", "#", "", "", "", ""]);
+    }
+
+    #[test]
+    fn heatmap_levels_relative_to_max() {
+        let from = date("2025-02-03");
+        let lines = heatmap(&ASCII, from, date("2025-02-09"), |day| {
+            i64::from(day.weekday().num_days_from_monday()) * 1800
+        });
+        // The maximum of 3 hours on sunday gets the highest level
+        assert_eq!(heatmap_days(&lines), [".", "-", "+", "+", "*", "#", "#"]);
+    }
+
+    #[test]
+    fn bar_chart_without_values() {
+        assert!(bar_chart(&ASCII, DurationFormat::default(), &[]).is_empty());
+    }
+
+    #[test]
+    fn bar_chart_single_value() {
+        let lines = bar_chart(&ASCII, DurationFormat::default(), &[("a".to_string(), 60)]);
+        assert_eq!(lines, [format!("a {} 0h 01m", "#".repeat(BAR_WIDTH))]);
+    }
+
+    #[test]
+    fn bar_chart_sorted_by_max_value() {
+        let values = [("short".to_string(), 1800), ("long".to_string(), 7200)];
+        let lines = bar_chart(&UNICODE, DurationFormat::default(), &values);
+        assert_eq!(
+            lines,
+            [
+                format!("long  {} 2h 00m", "█".repeat(BAR_WIDTH)),
+                format!(
+                    "short {}{} 0h 30m",
+                    "█".repeat(BAR_WIDTH / 4),
+                    " ".repeat(BAR_WIDTH - BAR_WIDTH / 4)
+                ),
+            ]
+        );
+    }
+}
@@ -1,5 +1,5 @@
-use crate::{Context, commands::OutputFormat};
+use crate::{Context, commands::OutputOptions};
 
 pub(super) trait CommandExecutorTrait {
-    async fn execute(&self, ctx: Context, output_format: OutputFormat) -> miette::Result<()>;
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()>;
 }
@@ -1,7 +1,10 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use chrono::{DateTime, Local, Utc};
 use serde::Serialize;
-use tabled::{Table, Tabled, settings::Style};
+use tabled::{Tabled, builder::Builder, settings::Style};
 
-use crate::commands::OutputFormat;
+use crate::commands::{OutputFormat, OutputOptions};
 
 pub(super) struct CommandOutput<I, TOut>
 where
@@ -12,7 +15,8 @@ where
     suffix_messages: Vec<String>,
     error_messages: Vec<String>,
     table_rows: Option<I>,
-    mode: OutputFormat,
+    hidden_columns: Vec<String>,
+    options: OutputOptions,
 }
 
 impl<I, TOut> CommandOutput<I, TOut>
@@ -26,20 +30,24 @@ where
             prefix_messages: Vec::new(),
             suffix_messages: Vec::new(),
             error_messages: Vec::new(),
-            mode: None,
+            hidden_columns: Vec::new(),
+            options: None,
         }
     }
 
-    pub fn print(&self) {
-        match self.mode {
-            OutputFormat::Json => self.json(),
-            OutputFormat::Yaml => self.yaml(),
-            OutputFormat::Toml => self.toml(),
-            OutputFormat::Human => self.human(),
+    pub fn print(&self) -> miette::Result<()> {
+        let rows = self.table_rows()?;
+        match self.options.format {
+            OutputFormat::Json => self.json(rows),
+            OutputFormat::Yaml => self.yaml(rows),
+            OutputFormat::Toml => self.toml(rows),
+            OutputFormat::Human => self.human(rows),
+            OutputFormat::Template => self.template(rows)?,
         }
+        Ok(())
     }
 
-    fn human(&self) {
+    fn human(&self, rows: Option<TableRows>) {
         // Only print error message and then abort
         if !self.error_messages.is_empty() {
             eprintln!("Errors:");
@@ -55,8 +63,13 @@ where
         }
 
         // Print the table if it exists
-        if let Some(table_rows) = &self.table_rows {
-            let mut table = Table::new(table_rows.clone());
+        if let Some(rows) = rows {
+            let mut builder = Builder::with_capacity(rows.cells.len() + 1, rows.headers.len());
+            builder.push_record(rows.headers);
+            for cells in rows.cells {
+                builder.push_record(cells);
+            }
+            let mut table = builder.build();
             table.with(Style::modern_rounded());
             println!("{table}");
         }
@@ -67,37 +80,143 @@ where
         }
     }
 
-    fn json(&self) {
-        let json_string = serde_json::to_string(&self.output_structure())
+    fn json(&self, rows: Option<TableRows>) {
+        let json_string = serde_json::to_string(&self.output_structure(rows))
             .expect("Failed to serialize output structure to JSON");
 
         // Print the JSON string
         println!("{json_string}");
     }
 
-    fn yaml(&self) {
-        let yaml_string = serde_yaml::to_string(&self.output_structure())
+    fn yaml(&self, rows: Option<TableRows>) {
+        let yaml_string = serde_yaml::to_string(&self.output_structure(rows))
             .expect("Failed to serialize output structure to YAML");
 
         // Print the YAML string
         println!("{yaml_string}");
     }
 
-    fn toml(&self) {
-        let toml_string = toml::to_string(&self.output_structure())
+    fn toml(&self, rows: Option<TableRows>) {
+        let mut output_structure = self.output_structure(rows);
+        // TOML has no null values, so empty cells are left out
+        for row in output_structure.table_rows.iter_mut().flatten() {
+            if let serde_json::Value::Object(object) = row {
+                object.retain(|_, value| !value.is_null());
+            }
+        }
+        let toml_string = toml::to_string(&output_structure)
             .expect("Failed to serialize output structure to TOML");
         // Print the TOML string
         println!("{toml_string}");
     }
 
-    fn output_structure(&self) -> OutputStructure<I> {
+    fn template(&self, rows: Option<TableRows>) -> miette::Result<()> {
+        let template = self
+            .options
+            .template
+            .as_ref()
+            .ok_or_else(|| miette::miette!("No template set for template output"))?;
+
+        if !self.error_messages.is_empty() {
+            for error in &self.error_messages {
+                eprintln!("{}", error);
+            }
+            return Ok(());
+        }
+
+        match rows {
+            Some(rows) => {
+                for row in &rows.values {
+                    println!("{}", template.render(row)?);
+                }
+            }
+            // Commands without a table expose their messages to the template
+            None => {
+                let messages = serde_json::json!({
+                    "message": self.prefix_messages.join("\n"),
+                });
+                println!("{}", template.render(&messages)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn output_structure(&self, rows: Option<TableRows>) -> OutputStructure {
         OutputStructure {
             prefix_messages: Some(self.prefix_messages.clone()),
             suffix_messages: Some(self.suffix_messages.clone()),
             error_messages: Some(self.error_messages.clone()),
-            table_rows: self.table_rows.clone(),
+            table_rows: rows.map(|rows| rows.values),
         }
     }
+
+    /// Apply the column selection, sorting and limit of the table options to the rows
+    fn table_rows(&self) -> miette::Result<Option<TableRows>> {
+        let Some(table_rows) = &self.table_rows else {
+            return Ok(None);
+        };
+        let table_options = &self.options.table;
+
+        // Resolve the selected columns to their position in the row
+        let headers = TOut::headers();
+        let column_indexes = match &table_options.columns {
+            Some(columns) => columns
+                .iter()
+                .map(|column| column_index(&headers, column))
+                .collect::<miette::Result<Vec<_>>>()?,
+            None => (0..headers.len())
+                .filter(|index| !self.hidden_columns.iter().any(|c| c == &headers[*index]))
+                .collect(),
+        };
+
+        let mut rows = table_rows
+            .clone()
+            .into_iter()
+            .map(|row| {
+                let value = serde_json::to_value(&row)
+                    .expect("Failed to serialize table row to JSON value");
+                (row, value)
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(sort_key) = &table_options.sort_by {
+            column_index(&headers, &sort_key.field)?;
+            rows.sort_by(|(_, a), (_, b)| {
+                let ordering = compare_values(&a[&sort_key.field], &b[&sort_key.field]);
+                if sort_key.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        if let Some(limit) = table_options.limit {
+            rows.truncate(limit);
+        }
+
+        let mut table_rows = TableRows {
+            headers: column_indexes
+                .iter()
+                .map(|index| headers[*index].to_string())
+                .collect(),
+            cells: Vec::with_capacity(rows.len()),
+            values: Vec::with_capacity(rows.len()),
+        };
+        for (row, value) in rows {
+            let fields = row.fields();
+            table_rows.cells.push(
+                column_indexes
+                    .iter()
+                    .map(|index| fields[*index].to_string())
+                    .collect(),
+            );
+            table_rows
+                .values
+                .push(select_columns(value, &table_rows.headers));
+        }
+        Ok(Some(table_rows))
+    }
 }
 
 pub(super) struct CommandOutputBuilder<I, TOut>
@@ -109,7 +228,8 @@ where
     prefix_messages: Vec<String>,
     suffix_messages: Vec<String>,
     error_messages: Vec<String>,
-    mode: Option<OutputFormat>,
+    hidden_columns: Vec<String>,
+    options: Option<OutputOptions>,
 }
 
 impl<I, TOut> CommandOutputBuilder<I, TOut>
@@ -127,7 +247,6 @@ where
         self
     }
 
-    #[allow(unused)]
     pub fn with_suffix_message(mut self, message: String) -> Self {
         self.suffix_messages.push(message);
         self
@@ -139,8 +258,15 @@ where
         self
     }
 
-    pub fn with_mode(mut self, mode: OutputFormat) -> Self {
-        self.mode = Some(mode);
+    /// Hide columns unless they are explicitly selected with the table options
+    pub fn with_hidden_columns(mut self, columns: &[&str]) -> Self {
+        self.hidden_columns
+            .extend(columns.iter().map(|column| column.to_string()));
+        self
+    }
+
+    pub fn with_options(mut self, options: &OutputOptions) -> Self {
+        self.options = Some(options.clone());
         self
     }
 
@@ -150,18 +276,115 @@ where
             prefix_messages: self.prefix_messages,
             suffix_messages: self.suffix_messages,
             error_messages: self.error_messages,
-            mode: self.mode.expect("Output format must be set"),
+            hidden_columns: self.hidden_columns,
+            options: self.options.expect("Output options must be set"),
+        }
+    }
+}
+
+/// Column selection, sorting and row limit applied to table output
+#[derive(Debug, Clone, Default)]
+pub(super) struct TableOptions {
+    pub(super) columns: Option<Vec<String>>,
+    pub(super) sort_by: Option<SortKey>,
+    pub(super) limit: Option<usize>,
+}
+
+/// Column to sort table rows by, parsed from `<field>[:asc|:desc]`
+#[derive(Debug, Clone)]
+pub(super) struct SortKey {
+    field: String,
+    descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, descending) = match s.rsplit_once(':') {
+            Some((field, "desc")) => (field, true),
+            Some((field, "asc")) => (field, false),
+            Some((_, direction)) => {
+                return Err(format!(
+                    "Invalid sort direction '{direction}', expected 'asc' or 'desc'"
+                ));
+            }
+            None => (s, false),
+        };
+        if field.is_empty() {
+            return Err("Sort field must not be empty".to_string());
         }
+        Ok(Self {
+            field: field.to_string(),
+            descending,
+        })
     }
 }
 
+/// Rows of a table after the table options have been applied
+struct TableRows {
+    headers: Vec<String>,
+    cells: Vec<Vec<String>>,
+    values: Vec<serde_json::Value>,
+}
+
+fn column_index(headers: &[std::borrow::Cow<'static, str>], column: &str) -> miette::Result<usize> {
+    headers
+        .iter()
+        .position(|header| header == column)
+        .ok_or_else(|| {
+            miette::miette!(
+                "Unknown column '{}', available columns: {}",
+                column,
+                headers.join(", ")
+            )
+        })
+}
+
+fn select_columns(value: serde_json::Value, columns: &[String]) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(mut object) => serde_json::Value::Object(
+            columns
+                .iter()
+                .filter_map(|column| object.shift_remove_entry(column))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Compare two serialized cell values, numbers numerically and strings lexically
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    use serde_json::Value;
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::String(a), Value::String(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Less,
+        (_, Value::Null) => Ordering::Greater,
+        (a, b) => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+/// Format a point in time in the local time zone
+pub(super) fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
 #[derive(Clone, Tabled, Serialize)]
 pub(super) struct NoTable;
 
 #[derive(Serialize)]
-struct OutputStructure<TRows> {
+struct OutputStructure {
     prefix_messages: Option<Vec<String>>,
     suffix_messages: Option<Vec<String>>,
     error_messages: Option<Vec<String>>,
-    table_rows: Option<TRows>,
+    table_rows: Option<Vec<serde_json::Value>>,
 }
@@ -21,7 +21,7 @@ impl CommandExecutorTrait for CompletionCommand {
     async fn execute(
         &self,
         _ctx: crate::Context,
-        _output_format: crate::commands::OutputFormat,
+        _output: &crate::commands::OutputOptions,
     ) -> miette::Result<()> {
         let mut command = Cli::command();
         match self {
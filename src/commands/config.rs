@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+use super::CommandExecutorTrait;
+use crate::{
+    Context,
+    commands::{
+        OutputOptions,
+        command_output::{CommandOutput, NoTable},
+    },
+    config,
+};
+
+#[derive(Subcommand)]
+pub(super) enum ConfigCommand {
+    /// Show the path of the config file
+    Path(ConfigPathCommand),
+    /// List the effective settings and where they come from
+    List(ListConfigCommand),
+    /// Show the effective value of a setting
+    Get(GetConfigCommand),
+    /// Change a setting in the config file
+    Set(SetConfigCommand),
+}
+
+#[derive(Parser)]
+pub(super) struct ConfigPathCommand;
+
+#[derive(Parser)]
+pub(super) struct ListConfigCommand;
+
+#[derive(Parser)]
+pub(super) struct GetConfigCommand {
+    /// Dotted name of the setting (e.g., sync.url)
+    key: String,
+}
+
+#[derive(Parser)]
+pub(super) struct SetConfigCommand {
+    /// Dotted name of the setting (e.g., sync.url)
+    key: String,
+    /// New value, parsed as TOML if possible (e.g., 30, true or '["alice", "bob"]')
+    value: String,
+}
+
+impl CommandExecutorTrait for ConfigCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        match self {
+            ConfigCommand::Path(_) => print_message(output, ctx.config.path.display().to_string()),
+            ConfigCommand::List(_) => list(&ctx, output),
+            ConfigCommand::Get(cmd) => get(&ctx, output, &cmd.key),
+            ConfigCommand::Set(cmd) => set(&ctx, output, &cmd.key, &cmd.value),
+        }
+    }
+}
+
+impl ConfigCommand {
+    /// Run the command on the raw settings of the config file, if the config can't be loaded
+    /// because of an invalid setting, so it can still be inspected and fixed
+    pub(super) fn execute_raw(
+        &self,
+        path: &Path,
+        output: &OutputOptions,
+        error: miette::Report,
+    ) -> miette::Result<()> {
+        if !matches!(self, ConfigCommand::Path(_)) {
+            eprintln!("Warning: {error}");
+        }
+        match self {
+            ConfigCommand::Path(_) => print_message(output, path.display().to_string()),
+            ConfigCommand::List(_) => {
+                let config_table = config::file_settings(path)?
+                    .into_iter()
+                    .map(|(key, value)| ConfigTable {
+                        key,
+                        value: display_value(&value),
+                        source: config::ConfigSource::File.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                CommandOutput::<Vec<ConfigTable>, ConfigTable>::builder()
+                    .with_table_rows(config_table)
+                    .with_options(output)
+                    .with_prefix_message(format!("Settings of {}", path.display()))
+                    .build()
+                    .print()?;
+                Ok(())
+            }
+            ConfigCommand::Get(cmd) => {
+                let value = config::file_settings(path)?
+                    .into_iter()
+                    .find(|(setting, _)| *setting == cmd.key)
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| {
+                        miette::miette!(
+                            "Setting '{}' has no value in the config file {}",
+                            cmd.key,
+                            path.display()
+                        )
+                    })?;
+                print_message(output, display_value(&value))
+            }
+            ConfigCommand::Set(cmd) => {
+                config::set_in_file(path, &cmd.key, &cmd.value)?;
+                print_message(
+                    output,
+                    format!("Setting '{}' saved to {}", cmd.key, path.display()),
+                )
+            }
+        }
+    }
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct ConfigTable {
+    key: String,
+    value: String,
+    source: String,
+}
+
+fn list(ctx: &Context, output: &OutputOptions) -> miette::Result<()> {
+    let config_table = ctx
+        .config
+        .settings()?
+        .into_iter()
+        .map(|(key, value)| ConfigTable {
+            source: ctx.config.source(&key).to_string(),
+            value: display_value(&value),
+            key,
+        })
+        .collect::<Vec<_>>();
+
+    CommandOutput::<Vec<ConfigTable>, ConfigTable>::builder()
+        .with_table_rows(config_table)
+        .with_options(output)
+        .with_prefix_message(format!("Settings of {}", ctx.config.path.display()))
+        .build()
+        .print()?;
+    Ok(())
+}
+
+fn get(ctx: &Context, output: &OutputOptions, key: &str) -> miette::Result<()> {
+    let value = ctx
+        .config
+        .settings()?
+        .into_iter()
+        .find(|(setting, _)| setting == key)
+        .map(|(_, value)| value)
+        .ok_or_else(|| {
+            miette::miette!(
+                help = "List the settings with a value with `clog config list`",
+                "Setting '{}' has no value",
+                key
+            )
+        })?;
+    print_message(output, display_value(&value))
+}
+
+fn set(ctx: &Context, output: &OutputOptions, key: &str, value: &str) -> miette::Result<()> {
+    config::set_in_file(&ctx.config.path, key, value)?;
+    let mut message = format!("Setting '{key}' saved to {}", ctx.config.path.display());
+    let source = ctx.config.source(key);
+    if !matches!(
+        source,
+        config::ConfigSource::File | config::ConfigSource::Default
+    ) {
+        message += &format!(", but it is overridden by {source}");
+    }
+    print_message(output, message)
+}
+
+/// Strings without quotes, everything else as TOML
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn print_message(output: &OutputOptions, message: String) -> miette::Result<()> {
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(message)
+        .build()
+        .print()?;
+    Ok(())
+}
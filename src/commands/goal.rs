@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use chrono::Datelike;
+use clap::Parser;
+use sea_orm::prelude::*;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::{
+    Context,
+    commands::{CommandExecutorTrait, OutputOptions, command_output::CommandOutput},
+    config::GoalsConfig,
+    duration::Duration,
+    entity::{projects, tasks, time_entries},
+};
+
+#[derive(Parser)]
+pub(super) struct GoalCommand {
+    /// Only show the goals of this project
+    #[clap(short, long)]
+    project_name: Option<String>,
+}
+
+impl CommandExecutorTrait for GoalCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        let goal_table = goal_progress(&ctx, output, self.project_name.as_deref()).await?;
+
+        let mut goal_output = CommandOutput::builder()
+            .with_options(output)
+            .with_prefix_message("Progress towards the daily and weekly goals".to_string());
+        if goal_table.is_empty() {
+            goal_output = goal_output.with_suffix_message(format!(
+                "No goals configured, set them in the [goals] section of {}",
+                ctx.config.path.display()
+            ));
+        } else {
+            goal_output = goal_output.with_table_rows(goal_table);
+        }
+        goal_output.build().print()?;
+        Ok(())
+    }
+}
+
+#[derive(Tabled, Serialize, Clone)]
+pub(super) struct GoalTable {
+    pub(super) scope: String,
+    pub(super) period: String,
+    pub(super) target: Duration,
+    pub(super) tracked: Duration,
+    pub(super) remaining: Duration,
+    pub(super) progress: Progress,
+}
+
+/// Share of a goal which is reached, displayed as a percentage
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Progress(f64);
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}%", self.0)
+    }
+}
+
+impl Serialize for Progress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_f64((self.0 * 10.0).round() / 10.0)
+    }
+}
+
+/// Compare the time tracked today and in the current week with the configured goals,
+/// time entries which are still running are counted up to now
+pub(super) async fn goal_progress(
+    ctx: &Context,
+    output: &OutputOptions,
+    project_name: Option<&str>,
+) -> miette::Result<Vec<GoalTable>> {
+    let today = chrono::Local::now().date_naive();
+    let monday = today - chrono::Days::new(u64::from(today.weekday().num_days_from_monday()));
+    let next_monday = monday + chrono::Days::new(7);
+
+    let project_names: HashMap<i32, String> = projects::Entity::find()
+        .all(&ctx.db)
+        .await
+        .map_err(|e| miette::miette!("Failed to fetch projects: {}", e))?
+        .into_iter()
+        .map(|project| (project.id, project.name))
+        .collect();
+    if let Some(project_name) = project_name
+        && !project_names.values().any(|name| name == project_name)
+    {
+        return Err(miette::miette!(
+            "Project with name '{}' not found",
+            project_name
+        ));
+    }
+
+    // The start times are stored in UTC, so the days around the local week are fetched
+    // and filtered by their local date
+    let week_entries = time_entries::Entity::find()
+        .filter(
+            time_entries::Column::StartTime
+                .gte(monday - chrono::Days::new(1))
+                .and(time_entries::Column::StartTime.lt(next_monday + chrono::Days::new(1))),
+        )
+        .find_also_related(tasks::Entity)
+        .all(&ctx.db)
+        .await
+        .map_err(|e| miette::miette!("Failed to fetch time entries: {}", e))?;
+
+    // Sum the tracked time of the day and week, in total and per project
+    let mut tracked: HashMap<Option<&str>, (i64, i64)> = HashMap::new();
+    for (entry, task) in &week_entries {
+        let day = entry.start_time.with_timezone(&chrono::Local).date_naive();
+        if day < monday || day >= next_monday {
+            continue;
+        }
+        let secs = tracked_secs(entry);
+        let is_today = day == today;
+        let project = task
+            .as_ref()
+            .and_then(|task| project_names.get(&task.project_id))
+            .map(String::as_str);
+        for scope in [None, project] {
+            let (day, week) = tracked.entry(scope).or_default();
+            *week += secs;
+            if is_today {
+                *day += secs;
+            }
+        }
+    }
+
+    let mut goals: Vec<(Option<&str>, &GoalsConfig)> = vec![];
+    if project_name.is_none() {
+        goals.push((None, &ctx.config.goals));
+    }
+    let mut project_goals = ctx
+        .config
+        .projects
+        .iter()
+        .filter(|(name, _)| project_name.is_none_or(|project_name| project_name == *name))
+        .map(|(name, project)| (Some(name.as_str()), &project.goals))
+        .collect::<Vec<_>>();
+    project_goals.sort_by_key(|(name, _)| *name);
+    goals.extend(project_goals);
+
+    let mut goal_table = vec![];
+    for (scope, goals_config) in goals {
+        let (day, week) = tracked.get(&scope).copied().unwrap_or_default();
+        let periods = [
+            ("day", goals_config.daily_hours, day),
+            ("week", goals_config.weekly_hours, week),
+        ];
+        for (period, target_hours, tracked_secs) in periods {
+            let Some(target_hours) = target_hours else {
+                continue;
+            };
+            let target_secs = (target_hours * 3600.0).round() as i64;
+            let progress = if target_secs > 0 {
+                tracked_secs as f64 / target_secs as f64 * 100.0
+            } else {
+                100.0
+            };
+            goal_table.push(GoalTable {
+                scope: scope.unwrap_or("all").to_string(),
+                period: period.to_string(),
+                target: Duration::new(target_secs, output.duration_format),
+                tracked: Duration::new(tracked_secs, output.duration_format),
+                remaining: Duration::new(
+                    (target_secs - tracked_secs).max(0),
+                    output.duration_format,
+                ),
+                progress: Progress(progress),
+            });
+        }
+    }
+    Ok(goal_table)
+}
+
+/// Tracked seconds of a time entry, running entries are counted up to now
+pub(super) fn tracked_secs(entry: &time_entries::Model) -> i64 {
+    match entry.end_time {
+        Some(_) => i64::from(entry.duration),
+        None => (chrono::Local::now().fixed_offset() - entry.start_time)
+            .num_seconds()
+            .max(0),
+    }
+}
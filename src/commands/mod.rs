@@ -1,27 +1,80 @@
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand, ValueEnum};
 
 use crate::{
     Context,
     commands::{
-        command_executor::CommandExecutorTrait, completion::CompletionCommand,
-        project::ProjectCommand, report::ReportCommand, sync::SyncCommand, task::TaskCommand,
+        backup::BackupCommand,
+        command_executor::CommandExecutorTrait,
+        command_output::{SortKey, TableOptions},
+        completion::CompletionCommand,
+        config::ConfigCommand,
+        goal::GoalCommand,
+        profile::ProfileCommand,
+        project::ProjectCommand,
+        report::{ReportCommand, TeamCommand},
+        status::StatusCommand,
+        sync::SyncCommand,
+        task::TaskCommand,
+        template::Template,
     },
+    config::{Config, ConfigOverride, config_path},
+    db,
+    duration::DurationFormat,
 };
 
+mod backup;
+mod chart;
 mod command_executor;
 mod command_output;
 mod completion;
+mod config;
+mod goal;
+mod profile;
 mod project;
 mod report;
+mod status;
 mod sync;
 mod task;
+mod template;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
     #[clap(short, long, default_value = "human")]
-    /// Set the output format (e.g., json, yaml, table, human)
+    /// Set the output format (e.g., json, yaml, table, human, template)
     output: OutputFormat,
+    /// Template rendered for every table row in template output (e.g., '{name}: {tasks}')
+    #[clap(long, global = true, conflicts_with = "template_file")]
+    template: Option<String>,
+    /// Read the template for template output from a file
+    #[clap(long, global = true)]
+    template_file: Option<PathBuf>,
+    /// Style used to display durations (overrides the config file)
+    #[clap(long, global = true)]
+    duration_format: Option<DurationFormat>,
+    /// Comma separated list of columns to show in table output (e.g., id,name)
+    #[clap(long, global = true, value_delimiter = ',')]
+    columns: Option<Vec<String>>,
+    /// Sort table rows by a column, append ':desc' for descending order (e.g., tasks:desc)
+    #[clap(long, global = true)]
+    sort_by: Option<SortKey>,
+    /// Maximum number of table rows to show
+    #[clap(long, global = true)]
+    limit: Option<usize>,
+    /// Config file to use instead of config.toml in the user config directory (or CLOG_CONFIG)
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+    /// Override a setting of the config file, can be repeated (e.g., sync.url=file:///mnt/clog)
+    #[clap(long = "set", global = true, value_name = "KEY=VALUE")]
+    set: Vec<ConfigOverride>,
+    /// Use the database and sync settings of a profile instead of the active one (or CLOG_PROFILE)
+    #[clap(long, global = true)]
+    profile: Option<String>,
+    /// Skip the automatic sync of the `sync.auto` settings for this command
+    #[clap(long, global = true)]
+    no_sync: bool,
     #[clap(subcommand)]
     command: Command,
 }
@@ -32,6 +85,16 @@ enum OutputFormat {
     Yaml,
     Toml,
     Human,
+    Template,
+}
+
+/// Options controlling how the output of a command is rendered
+#[derive(Debug, Clone)]
+struct OutputOptions {
+    format: OutputFormat,
+    table: TableOptions,
+    template: Option<Template>,
+    duration_format: DurationFormat,
 }
 
 #[derive(Subcommand)]
@@ -45,28 +108,142 @@ enum Command {
     /// Interaction with project reports
     #[clap(subcommand)]
     Report(ReportCommand),
-    /// Interactions with the remote storage
-    #[clap(subcommand)]
+    /// Show the progress towards the daily and weekly goals
+    Goal(GoalCommand),
+    /// Show the task in progress and the progress towards the goals
+    Status(StatusCommand),
+    /// Merge the local and remote changes, or interact with the remote storage
     Sync(SyncCommand),
+    /// Create, list and restore backups of the local database
+    #[clap(subcommand)]
+    Backup(BackupCommand),
+    /// Reports across the databases of a team
+    #[clap(subcommand)]
+    Team(TeamCommand),
+    /// Show and change the settings of the config file
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+    /// Create, list and switch between profiles with their own database
+    #[clap(subcommand)]
+    Profile(ProfileCommand),
     /// Setup Shell Completion
     #[clap(subcommand)]
     Completion(CompletionCommand),
 }
 
 impl CommandExecutorTrait for Command {
-    async fn execute(&self, ctx: Context, output_format: OutputFormat) -> miette::Result<()> {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
         match self {
-            Command::Project(cmd) => cmd.execute(ctx, output_format).await,
-            Command::Task(cmd) => cmd.execute(ctx, output_format).await,
-            Command::Report(cmd) => cmd.execute(ctx, output_format).await,
-            Command::Sync(cmd) => cmd.execute(ctx, output_format).await,
-            Command::Completion(cmd) => cmd.execute(ctx, output_format).await,
+            Command::Project(cmd) => cmd.execute(ctx, output).await,
+            Command::Task(cmd) => cmd.execute(ctx, output).await,
+            Command::Report(cmd) => cmd.execute(ctx, output).await,
+            Command::Goal(cmd) => cmd.execute(ctx, output).await,
+            Command::Status(cmd) => cmd.execute(ctx, output).await,
+            Command::Sync(cmd) => cmd.execute(ctx, output).await,
+            Command::Backup(cmd) => cmd.execute(ctx, output).await,
+            Command::Team(cmd) => cmd.execute(ctx, output).await,
+            Command::Config(cmd) => cmd.execute(ctx, output).await,
+            Command::Profile(cmd) => cmd.execute(ctx, output).await,
+            Command::Completion(cmd) => cmd.execute(ctx, output).await,
         }
     }
 }
 
-pub(crate) async fn invoke(ctx: Context) -> miette::Result<()> {
+impl Command {
+    /// Whether the command changes the local database, so it is wrapped by the automatic sync
+    fn is_mutating(&self) -> bool {
+        match self {
+            Command::Project(cmd) => !matches!(cmd, ProjectCommand::List(_)),
+            Command::Task(cmd) => !matches!(cmd, TaskCommand::List(_)),
+            Command::Backup(cmd) => matches!(cmd, BackupCommand::Restore(_)),
+            _ => false,
+        }
+    }
+
+    /// Whether the remote changes are pulled before the command. A pull before restoring
+    /// a backup would create the newest backup itself, so the restore only pushes.
+    fn pulls_first(&self) -> bool {
+        self.is_mutating() && !matches!(self, Command::Backup(BackupCommand::Restore(_)))
+    }
+
+    /// Whether queued changes are pushed before the command. Syncing, the settings and the
+    /// shell completion work without it.
+    fn auto_syncs(&self) -> bool {
+        !matches!(
+            self,
+            Command::Sync(_) | Command::Config(_) | Command::Profile(_) | Command::Completion(_)
+        )
+    }
+}
+
+pub(crate) async fn invoke() -> miette::Result<()> {
     let cli = Cli::parse();
+    let config_path = match &cli.config {
+        Some(config_path) => config_path.clone(),
+        None => match std::env::var("CLOG_CONFIG") {
+            Ok(config_path) if !config_path.is_empty() => PathBuf::from(config_path),
+            _ => config_path()?,
+        },
+    };
+    let template = match (&cli.template, &cli.template_file) {
+        (Some(template), _) => Some(template.clone()),
+        (None, Some(path)) => Some(std::fs::read_to_string(path).map_err(|e| {
+            miette::miette!("Failed to read template file {}: {}", path.display(), e)
+        })?),
+        (None, None) => None,
+    };
+    let template = match (cli.output, template) {
+        (OutputFormat::Template, Some(template)) => {
+            Some(Template::parse(template.trim_end_matches(['\r', '\n']))?)
+        }
+        (OutputFormat::Template, None) => {
+            return Err(miette::miette!(
+                "Template output requires --template or --template-file"
+            ));
+        }
+        (_, Some(_)) => {
+            return Err(miette::miette!(
+                "--template and --template-file can only be used with '-o template'"
+            ));
+        }
+        (_, None) => None,
+    };
+
+    let mut output = OutputOptions {
+        format: cli.output,
+        table: TableOptions {
+            columns: cli.columns,
+            sort_by: cli.sort_by,
+            limit: cli.limit,
+        },
+        template,
+        duration_format: cli.duration_format.unwrap_or_default(),
+    };
+    let config = match Config::load(&config_path, cli.profile.as_deref(), &cli.set) {
+        Ok(config) => config,
+        // The config commands work on the file itself, so an invalid setting can be fixed
+        Err(e) => match &cli.command {
+            Command::Config(cmd) => return cmd.execute_raw(&config_path, &output, e),
+            _ => return Err(e),
+        },
+    };
+    if let (None, Some(duration_format)) = (cli.duration_format, config.output.duration_format) {
+        output.duration_format = duration_format;
+    }
 
-    cli.command.execute(ctx, cli.output).await
+    // The arguments are validated before the automatic sync contacts the remote storage
+    let auto_sync = config.sync.auto.enabled && !cli.no_sync && cli.command.auto_syncs();
+    let mut db = db::connect(&config).await?;
+    if auto_sync {
+        db = sync::auto_sync_before(db, &config, cli.command.pulls_first()).await?;
+    }
+    // The command consumes the context, so the config is kept for the push afterwards
+    let auto_sync_config = (auto_sync && cli.command.is_mutating()).then(|| config.clone());
+    let ctx = Context::new(db, config);
+
+    cli.command.execute(ctx, &output).await?;
+    if let Some(config) = auto_sync_config {
+        sync::auto_sync_after(&config).await?;
+    }
+    Ok(())
 }
@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+use super::CommandExecutorTrait;
+use crate::{
+    Context,
+    commands::{
+        OutputOptions,
+        command_output::{CommandOutput, NoTable},
+    },
+    config::{self, Config, ConfigSource, DEFAULT_PROFILE},
+    db,
+};
+
+#[derive(Subcommand)]
+pub(super) enum ProfileCommand {
+    /// List the profiles with their database and sync URL
+    List(ListProfilesCommand),
+    /// Create a profile with its own database
+    Create(CreateProfileCommand),
+    /// Switch to another profile, which is saved in the config file
+    Use(UseProfileCommand),
+}
+
+#[derive(Parser)]
+pub(super) struct ListProfilesCommand;
+
+#[derive(Parser)]
+pub(super) struct CreateProfileCommand {
+    /// Name of the profile (e.g., personal)
+    name: String,
+    /// Database of the profile, instead of profiles/<name>.sqlite in the user data directory
+    #[clap(long)]
+    database: Option<PathBuf>,
+    /// URL of the remote storage the profile is synced with (e.g., file:///mnt/share/personal)
+    #[clap(long)]
+    sync_url: Option<String>,
+}
+
+#[derive(Parser)]
+pub(super) struct UseProfileCommand {
+    /// Name of the profile, 'default' for the settings without a profile
+    name: String,
+}
+
+impl CommandExecutorTrait for ProfileCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        match self {
+            ProfileCommand::List(_) => list(&ctx, output),
+            ProfileCommand::Create(cmd) => create(&ctx, output, cmd),
+            ProfileCommand::Use(cmd) => use_profile(&ctx, output, &cmd.name),
+        }
+    }
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct ProfileTable {
+    name: String,
+    database: String,
+    sync_url: String,
+    active: bool,
+}
+
+fn list(ctx: &Context, output: &OutputOptions) -> miette::Result<()> {
+    let active_profile = ctx.config.active_profile().unwrap_or(DEFAULT_PROFILE);
+    let profile_table = std::iter::once(DEFAULT_PROFILE)
+        .chain(ctx.config.profiles.keys().map(String::as_str))
+        .map(|name| {
+            // The settings each profile would have, including the environment variables
+            let profile_config = Config::load(&ctx.config.path, Some(name), &[])?;
+            Ok(ProfileTable {
+                name: name.to_string(),
+                database: db::db_path(&profile_config)?.display().to_string(),
+                sync_url: profile_config.sync.url.unwrap_or_default(),
+                active: name == active_profile,
+            })
+        })
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    CommandOutput::<Vec<ProfileTable>, ProfileTable>::builder()
+        .with_table_rows(profile_table)
+        .with_options(output)
+        .with_prefix_message(format!("Profiles of {}", ctx.config.path.display()))
+        .build()
+        .print()?;
+    Ok(())
+}
+
+fn create(ctx: &Context, output: &OutputOptions, cmd: &CreateProfileCommand) -> miette::Result<()> {
+    let name = cmd.name.as_str();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(miette::miette!(
+            help = "Use letters, digits, '-' and '_'",
+            "Invalid profile name '{}'",
+            name
+        ));
+    }
+    if name == DEFAULT_PROFILE || ctx.config.profiles.contains_key(name) {
+        return Err(miette::miette!("Profile '{}' already exists", name));
+    }
+
+    let database = match &cmd.database {
+        // Relative paths would depend on the directory clog is run in
+        Some(database) => std::path::absolute(database)
+            .map_err(|e| miette::miette!("Invalid database path {}: {}", database.display(), e))?,
+        None => db::default_db_path(Some(name))?,
+    };
+    let database = database
+        .to_str()
+        .ok_or_else(|| miette::miette!("Invalid database path: {}", database.display()))?;
+    config::write_setting(
+        &ctx.config.path,
+        &format!("profiles.{name}.database"),
+        database.into(),
+    )?;
+    if let Some(sync_url) = &cmd.sync_url {
+        config::write_setting(
+            &ctx.config.path,
+            &format!("profiles.{name}.sync.url"),
+            sync_url.as_str().into(),
+        )?;
+    }
+    print_message(
+        output,
+        format!(
+            "Profile '{name}' created with the database {database}, switch to it with `clog profile use {name}`"
+        ),
+    )
+}
+
+fn use_profile(ctx: &Context, output: &OutputOptions, name: &str) -> miette::Result<()> {
+    if name != DEFAULT_PROFILE && !ctx.config.profiles.contains_key(name) {
+        return Err(miette::miette!(
+            help = "List the profiles with `clog profile list`",
+            "Profile '{}' doesn't exist",
+            name
+        ));
+    }
+    config::write_setting(&ctx.config.path, "profile", name.into())?;
+
+    let mut message = format!("Switched to profile '{name}'");
+    for key in ["profile", "database"] {
+        let source = ctx.config.source(key);
+        if matches!(source, ConfigSource::Env(_) | ConfigSource::Cli) {
+            message += &format!(", but the {key} is overridden by {source}");
+        }
+    }
+    print_message(output, message)
+}
+
+fn print_message(output: &OutputOptions, message: String) -> miette::Result<()> {
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(message)
+        .build()
+        .print()?;
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use clap::{Parser, Subcommand};
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, IntoActiveModel, TryIntoModel, prelude::*};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, FromQueryResult, IntoActiveModel, QueryOrder, QuerySelect,
+    TryIntoModel, prelude::*,
+};
 use serde::Serialize;
 use tabled::Tabled;
 
@@ -7,10 +10,10 @@ use super::CommandExecutorTrait;
 use crate::{
     Context,
     commands::{
-        OutputFormat,
+        OutputOptions,
         command_output::{CommandOutput, NoTable},
     },
-    entity::projects,
+    entity::{projects, tasks},
 };
 
 #[derive(Subcommand)]
@@ -33,6 +36,9 @@ pub(super) struct CreateProjectCommand {
     /// Description of the project
     #[clap(short, long)]
     description: Option<String>,
+    /// Client the project is billed to, reports can group the projects by it
+    #[clap(short, long)]
+    client: Option<String>,
 }
 
 #[derive(Parser)]
@@ -53,26 +59,37 @@ pub(super) struct EditProjectCommand {
     /// New description for the project
     #[clap(short = 'd', long)]
     new_description: Option<String>,
+    /// New client for the project
+    #[clap(short = 'c', long)]
+    new_client: Option<String>,
 }
 
 #[derive(Parser)]
 pub(super) struct ListProjectsCommand;
 
 impl CommandExecutorTrait for ProjectCommand {
-    async fn execute(&self, ctx: Context, output_format: OutputFormat) -> miette::Result<()> {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
         match self {
             ProjectCommand::Create(cmd) => {
-                create(&ctx, &cmd.name, cmd.description.as_deref(), output_format).await
+                create(
+                    &ctx,
+                    &cmd.name,
+                    cmd.description.as_deref(),
+                    cmd.client.as_deref(),
+                    output,
+                )
+                .await
             }
-            ProjectCommand::Remove(cmd) => remove(&ctx, &cmd.name, output_format).await,
-            ProjectCommand::List(_) => list(&ctx, output_format).await,
+            ProjectCommand::Remove(cmd) => remove(&ctx, &cmd.name, output).await,
+            ProjectCommand::List(_) => list(&ctx, output).await,
             ProjectCommand::Edit(cmd) => {
                 edit(
                     &ctx,
                     &cmd.name,
                     cmd.new_name.as_deref(),
                     cmd.new_description.as_deref(),
-                    output_format,
+                    cmd.new_client.as_deref(),
+                    output,
                 )
                 .await
             }
@@ -84,11 +101,13 @@ async fn create(
     ctx: &Context,
     name: &str,
     description: Option<&str>,
-    output_format: OutputFormat,
+    client: Option<&str>,
+    output: &OutputOptions,
 ) -> miette::Result<()> {
     let created_project = projects::ActiveModel {
         name: Set(name.to_string()),
         description: Set(description.map(|d| d.to_string())),
+        client: Set(client.map(|c| c.to_string())),
         ..Default::default()
     }
     .save(&ctx.db)
@@ -98,17 +117,17 @@ async fn create(
     .map_err(|e| miette::miette!("Failed to convert active model to project model: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message(format!(
             "Project '{name}' created successfully",
             name = created_project.name
         ))
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
-async fn remove(ctx: &Context, name: &str, output_format: OutputFormat) -> miette::Result<()> {
+async fn remove(ctx: &Context, name: &str, output: &OutputOptions) -> miette::Result<()> {
     let project = projects::Entity::find()
         .filter(projects::Column::Name.eq(name))
         .one(&ctx.db)
@@ -121,44 +140,57 @@ async fn remove(ctx: &Context, name: &str, output_format: OutputFormat) -> miett
         .map_err(|e| miette::miette!("Failed to remove project: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message("Project was removed successfully".to_string())
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
-async fn list(ctx: &Context, output_format: OutputFormat) -> miette::Result<()> {
-    let projects = projects::Entity::find()
+async fn list(ctx: &Context, output: &OutputOptions) -> miette::Result<()> {
+    // Count the tasks of all projects in a single grouped query
+    let projects_table = projects::Entity::find()
+        .select_only()
+        .columns([
+            projects::Column::Id,
+            projects::Column::Name,
+            projects::Column::Description,
+        ])
+        .column_as(tasks::Column::Id.into_expr().count(), "tasks")
+        .left_join(tasks::Entity)
+        .group_by(projects::Column::Id)
+        .order_by_asc(projects::Column::Id)
+        .into_model::<ProjectWithTaskCount>()
         .all(&ctx.db)
         .await
-        .map_err(|e| miette::miette!("Failed to list projects: {}", e))?;
-
-    let mut projects_table: Vec<ProjectTable> = vec![];
-    for project in projects {
-        let amount_of_tasks = project
-            .find_related(crate::entity::tasks::Entity)
-            .count(&ctx.db)
-            .await
-            .map_err(|e| miette::miette!("Failed to count tasks: {}", e))?;
-        projects_table.push(ProjectTable {
+        .map_err(|e| miette::miette!("Failed to list projects: {}", e))?
+        .into_iter()
+        .map(|project| ProjectTable {
             id: project.id,
             name: project.name,
             description: project.description.unwrap_or_default(),
-            tasks: amount_of_tasks,
-        });
-    }
+            tasks: project.tasks as u64,
+        })
+        .collect::<Vec<_>>();
 
     CommandOutput::<Vec<ProjectTable>, ProjectTable>::builder()
         .with_table_rows(projects_table)
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message("All Projects in the database".to_string())
         .build()
-        .print();
+        .print()?;
 
     Ok(())
 }
 
+#[derive(FromQueryResult)]
+struct ProjectWithTaskCount {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    tasks: i64,
+}
+
 #[derive(Tabled, Serialize, Clone)]
 struct ProjectTable {
     id: i32,
@@ -172,7 +204,8 @@ async fn edit(
     name: &str,
     new_name: Option<&str>,
     new_description: Option<&str>,
-    output_format: OutputFormat,
+    new_client: Option<&str>,
+    output: &OutputOptions,
 ) -> miette::Result<()> {
     let mut project = projects::Entity::find()
         .filter(projects::Column::Name.eq(name))
@@ -188,6 +221,9 @@ async fn edit(
     if let Some(new_description) = new_description {
         project.description = Set(Some(new_description.to_string()));
     }
+    if let Some(new_client) = new_client {
+        project.client = Set(Some(new_client.to_string()));
+    }
 
     let project = project
         .update(&ctx.db)
@@ -195,9 +231,9 @@ async fn edit(
         .map_err(|e| miette::miette!("Failed to update project: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message(format!("Project '{}' updated successfully", project.name))
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
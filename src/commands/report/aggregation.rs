@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clap::ValueEnum;
+use sea_orm::{
+    JoinType, QuerySelect, QueryTrait,
+    prelude::*,
+    sea_query::{Alias, Expr, Func, IntoIden, SimpleExpr, TableRef},
+};
+use serde::Serialize;
+use tabled::Tabled;
+
+use super::ReportWindow;
+use crate::{
+    commands::OutputOptions,
+    duration::{Duration, DurationFormat},
+    entity::{projects, tasks, time_entries},
+};
+
+/// Dimension the time entries of a report can be grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(super) enum Dimension {
+    Client,
+    Project,
+    Task,
+    Day,
+    Week,
+    Month,
+    Tag,
+}
+
+impl Dimension {
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            Dimension::Client => "client",
+            Dimension::Project => "project",
+            Dimension::Task => "task",
+            Dimension::Day => "day",
+            Dimension::Week => "week",
+            Dimension::Month => "month",
+            Dimension::Tag => "tag",
+        }
+    }
+
+    /// Expressions the time entries are grouped by, the label of the group and the id of the
+    /// grouped project or task
+    fn columns(&self) -> (SimpleExpr, Option<SimpleExpr>) {
+        match self {
+            Dimension::Client => (
+                Func::coalesce([
+                    Expr::col((projects::Entity, projects::Column::Client)).into(),
+                    Expr::val(NO_GROUP).into(),
+                ])
+                .into(),
+                None,
+            ),
+            Dimension::Project => (
+                Expr::col((projects::Entity, projects::Column::Name)).into(),
+                Some(Expr::col((projects::Entity, projects::Column::Id)).into()),
+            ),
+            Dimension::Task => (
+                Expr::col((tasks::Entity, tasks::Column::Name)).into(),
+                Some(Expr::col((tasks::Entity, tasks::Column::Id)).into()),
+            ),
+            Dimension::Day => (local_time("date(", ")"), None),
+            Dimension::Week => (local_time("strftime('%G-W%V', ", ")"), None),
+            Dimension::Month => (local_time("strftime('%Y-%m', ", ")"), None),
+            Dimension::Tag => (
+                Func::coalesce([
+                    Expr::col((Alias::new(TASK_TAGS), Alias::new("value"))).into(),
+                    Expr::val(NO_GROUP).into(),
+                ])
+                .into(),
+                None,
+            ),
+        }
+    }
+}
+
+/// Label of the group of the projects without a client or the tasks without tags
+const NO_GROUP: &str = "-";
+/// Alias of the tags of a task expanded into one row per tag
+const TASK_TAGS: &str = "task_tags";
+
+/// SQLite function of the start time of a time entry in the local time zone, as the
+/// report days are local days
+fn local_time(function: &str, suffix: &str) -> SimpleExpr {
+    Expr::cust(format!(
+        "{function}\"time_entries\".\"start_time\", 'localtime'{suffix}"
+    ))
+}
+
+/// Local day a time entry is reported on
+pub(super) fn local_day() -> SimpleExpr {
+    local_time("date(", ")")
+}
+
+/// Identifies a group, ordered by its label and distinguished by the id of the grouped entity
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct GroupKey {
+    label: String,
+    id: i32,
+}
+
+impl GroupKey {
+    pub(super) fn new(label: &str, id: i32) -> Self {
+        Self {
+            label: label.to_string(),
+            id,
+        }
+    }
+
+    pub(super) fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Id of the grouped project or task, 0 for the other dimensions
+    pub(super) fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Key which only distinguishes groups by their label, e.g. to combine the projects
+    /// of the same name from different databases
+    pub(super) fn by_label(&self) -> Self {
+        Self::new(&self.label, 0)
+    }
+}
+
+/// Groups of every level of nesting, keyed by the leading group keys of the level:
+/// the total first, then the groups of the first dimension and so on
+pub(super) type GroupLevels = Vec<BTreeMap<Vec<GroupKey>, GroupStats>>;
+
+/// Aggregated values of a group of time entries
+#[derive(Debug, Clone, Default)]
+pub(super) struct GroupStats {
+    pub(super) time_spent_secs: i64,
+    pub(super) time_entries: usize,
+    pub(super) open_time_entries: bool,
+    /// UUIDs of the tasks, which are unique across databases
+    pub(super) tasks: BTreeSet<String>,
+}
+
+impl GroupStats {
+    pub(super) fn add(&mut self, other: &GroupStats) {
+        self.time_spent_secs += other.time_spent_secs;
+        self.time_entries += other.time_entries;
+        self.open_time_entries |= other.open_time_entries;
+        self.tasks.extend(other.tasks.iter().cloned());
+    }
+}
+
+/// Aggregate the time entries within the window by the combination of dimensions,
+/// returning one entry per distinct combination of group keys. The sums are grouped by
+/// the task as well in SQL, so the tasks of a group can be counted across subtotals.
+pub(super) async fn aggregate(
+    db: &DatabaseConnection,
+    window: &ReportWindow,
+    project_name: Option<&str>,
+    dimensions: &[Dimension],
+) -> miette::Result<BTreeMap<Vec<GroupKey>, GroupStats>> {
+    if let Some(project_name) = project_name {
+        projects::Entity::find()
+            .filter(projects::Column::Name.eq(project_name))
+            .one(db)
+            .await
+            .map_err(|e| miette::miette!("Failed to fetch projects: {}", e))?
+            .ok_or_else(|| miette::miette!("Project with name '{}' not found", project_name))?;
+    }
+
+    let mut query = time_entries::Entity::find()
+        .select_only()
+        .join(JoinType::InnerJoin, time_entries::Relation::Tasks.def())
+        .join(JoinType::InnerJoin, tasks::Relation::Projects.def());
+    // The time entries of a task are counted once for every tag of the task
+    if dimensions.contains(&Dimension::Tag) {
+        let tags = Func::cust(Alias::new("json_each"))
+            .arg(Expr::col((tasks::Entity, tasks::Column::Tags)));
+        QueryTrait::query(&mut query).join(
+            JoinType::LeftJoin,
+            TableRef::FunctionCall(tags, Alias::new(TASK_TAGS).into_iden()),
+            Expr::cust("TRUE"),
+        );
+    }
+    let columns = dimensions
+        .iter()
+        .map(Dimension::columns)
+        .collect::<Vec<_>>();
+    for (index, (label, id)) in columns.iter().cloned().enumerate() {
+        query = query
+            .column_as(label.clone(), format!("label_{index}"))
+            .group_by(label);
+        if let Some(id) = id {
+            query = query
+                .column_as(id.clone(), format!("id_{index}"))
+                .group_by(id);
+        }
+    }
+    query = query
+        .column_as(tasks::Column::Uuid, "task_uuid")
+        .column_as(time_entries::Column::Duration.sum(), "time_spent")
+        .column_as(time_entries::Column::Id.count(), "time_entries")
+        .column_as(time_entries::Column::EndTime.count(), "closed_time_entries")
+        .group_by(tasks::Column::Uuid);
+    if let Some(project_name) = project_name {
+        query = query.filter(projects::Column::Name.eq(project_name));
+    }
+    let statement = window.filter(query).build(db.get_database_backend());
+    let rows = db
+        .query_all(statement)
+        .await
+        .map_err(|e| miette::miette!("Failed to sum time entries: {}", e))?;
+
+    let read_error = |e: DbErr| miette::miette!("Failed to read summed time entries: {}", e);
+    let mut groups: BTreeMap<Vec<GroupKey>, GroupStats> = BTreeMap::new();
+    for row in rows {
+        let mut keys = Vec::with_capacity(columns.len());
+        for (index, (_, id)) in columns.iter().enumerate() {
+            let label: String = row
+                .try_get("", &format!("label_{index}"))
+                .map_err(read_error)?;
+            let id = match id {
+                Some(_) => row
+                    .try_get("", &format!("id_{index}"))
+                    .map_err(read_error)?,
+                None => 0,
+            };
+            keys.push(GroupKey::new(&label, id));
+        }
+        let time_spent: Option<i64> = row.try_get("", "time_spent").map_err(read_error)?;
+        let time_entries: i64 = row.try_get("", "time_entries").map_err(read_error)?;
+        let closed_time_entries: i64 =
+            row.try_get("", "closed_time_entries").map_err(read_error)?;
+        let stats = GroupStats {
+            time_spent_secs: time_spent.unwrap_or_default(),
+            time_entries: time_entries as usize,
+            open_time_entries: closed_time_entries < time_entries,
+            tasks: BTreeSet::from([row.try_get("", "task_uuid").map_err(read_error)?]),
+        };
+        groups.entry(keys).or_default().add(&stats);
+    }
+    Ok(groups)
+}
+
+/// Aggregate the time entries by every leading combination of the dimensions, from the total
+/// to the innermost groups, so every subtotal is summed in SQL as well. A time entry is only
+/// counted once by the subtotals above the tag dimension, even if its task has several tags.
+pub(super) async fn aggregate_levels(
+    db: &DatabaseConnection,
+    window: &ReportWindow,
+    project_name: Option<&str>,
+    dimensions: &[Dimension],
+) -> miette::Result<GroupLevels> {
+    let mut levels = Vec::with_capacity(dimensions.len() + 1);
+    for depth in 0..=dimensions.len() {
+        levels.push(aggregate(db, window, project_name, &dimensions[..depth]).await?);
+    }
+    Ok(levels)
+}
+
+/// Sum the innermost groups up to the groups of every level of nesting above them
+pub(super) fn sum_levels(groups: BTreeMap<Vec<GroupKey>, GroupStats>, depth: usize) -> GroupLevels {
+    let mut levels: GroupLevels = vec![BTreeMap::new(); depth];
+    for (keys, stats) in &groups {
+        for (level_depth, level) in levels.iter_mut().enumerate() {
+            level
+                .entry(keys[..level_depth].to_vec())
+                .or_default()
+                .add(stats);
+        }
+    }
+    levels.push(groups);
+    levels
+}
+
+#[derive(Tabled, Serialize, Clone)]
+pub(super) struct ReportGroupTable {
+    group: GroupLabel,
+    dimension: String,
+    depth: usize,
+    time_spent: Duration,
+    tasks: usize,
+    time_entries: usize,
+    open_time_entries: bool,
+}
+
+/// Label of a group, indented by its depth for human readable output
+#[derive(Debug, Clone)]
+struct GroupLabel {
+    label: String,
+    depth: usize,
+}
+
+impl std::fmt::Display for GroupLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", "  ".repeat(self.depth), self.label)
+    }
+}
+
+impl Serialize for GroupLabel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.label)
+    }
+}
+
+/// Flatten the groups of every level into rows with a subtotal for every level of nesting,
+/// followed by the total of all groups
+pub(super) fn nested_rows(
+    levels: &GroupLevels,
+    dimension_names: &[&str],
+    output: &OutputOptions,
+) -> Vec<ReportGroupTable> {
+    let mut rows = vec![];
+    push_nested_rows(
+        &mut rows,
+        levels,
+        &[],
+        dimension_names,
+        output.duration_format,
+    );
+
+    let total = levels
+        .first()
+        .and_then(|total| total.get(&vec![]))
+        .cloned()
+        .unwrap_or_default();
+    rows.push(group_row(
+        "Total",
+        "total",
+        0,
+        &total,
+        output.duration_format,
+    ));
+    rows
+}
+
+fn push_nested_rows(
+    rows: &mut Vec<ReportGroupTable>,
+    levels: &GroupLevels,
+    parent: &[GroupKey],
+    dimension_names: &[&str],
+    duration_format: DurationFormat,
+) {
+    let depth = parent.len();
+    let (Some(dimension_name), Some(level)) = (dimension_names.get(depth), levels.get(depth + 1))
+    else {
+        return;
+    };
+    // Groups are sorted by their keys, so all subgroups of the parent group are adjacent
+    for (keys, stats) in level
+        .range(parent.to_vec()..)
+        .take_while(|(keys, _)| keys.starts_with(parent))
+    {
+        rows.push(group_row(
+            &keys[depth].label,
+            dimension_name,
+            depth,
+            stats,
+            duration_format,
+        ));
+        push_nested_rows(rows, levels, keys, dimension_names, duration_format);
+    }
+}
+
+fn group_row(
+    label: &str,
+    dimension: &str,
+    depth: usize,
+    stats: &GroupStats,
+    duration_format: DurationFormat,
+) -> ReportGroupTable {
+    ReportGroupTable {
+        group: GroupLabel {
+            label: label.to_string(),
+            depth,
+        },
+        dimension: dimension.to_string(),
+        depth,
+        time_spent: Duration::new(stats.time_spent_secs, duration_format),
+        tasks: stats.tasks.len(),
+        time_entries: stats.time_entries,
+        open_time_entries: stats.open_time_entries,
+    }
+}
@@ -0,0 +1,810 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use clap::{Parser, Subcommand, ValueEnum};
+use sea_orm::{QueryOrder, QuerySelect, prelude::*, sea_query::Expr};
+use serde::Serialize;
+use tabled::Tabled;
+
+use self::aggregation::{Dimension, GroupKey, GroupStats};
+pub(super) use self::team::TeamCommand;
+use crate::{
+    Context,
+    commands::{
+        CommandExecutorTrait, OutputFormat, OutputOptions,
+        chart::{self, ChartStyle},
+        command_output::{CommandOutput, NoTable},
+    },
+    duration::Duration,
+    entity::{projects, tasks, time_entries},
+};
+
+mod aggregation;
+mod team;
+
+#[derive(Subcommand)]
+pub(super) enum ReportCommand {
+    /// Generate a report for the current project
+    Project(ReportProjectCommand),
+    /// Generate a report for a task in a project
+    Task(ReportTaskCommand),
+    /// Generate a weekly timesheet with the time spent per task and day
+    Timesheet(ReportTimesheetCommand),
+    /// Draw a heatmap of the daily tracked time and a bar chart of the time per project or task
+    Chart(ReportChartCommand),
+}
+
+#[derive(Parser)]
+pub(super) struct ReportProjectCommand {
+    /// Whether to gennerate the report for today
+    #[clap(short, long)]
+    today: bool,
+    /// The date to generate the report for (format: YYYY-MM-DD)
+    #[clap(short, long, conflicts_with = "today")]
+    date: Option<String>,
+    /// The ISO week to generate the report for (format: YYYY-Www)
+    #[clap(short, long, conflicts_with_all = ["today", "date"])]
+    week: Option<String>,
+    /// Compare with the 'previous' period or a specific one (format: YYYY-MM-DD or YYYY-Www)
+    #[clap(short, long)]
+    compare: Option<String>,
+    /// Group the time entries by a comma separated list of dimensions with nested subtotals
+    #[clap(
+        short,
+        long,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with = "compare"
+    )]
+    group_by: Option<Vec<Dimension>>,
+}
+
+#[derive(Parser)]
+pub(super) struct ReportTaskCommand {
+    /// The name of the project the task belongs to
+    #[clap(short, long)]
+    project_name: String,
+    /// Whether to gennerate the report for today
+    #[clap(short, long)]
+    today: bool,
+    /// The date to generate the report for (format: YYYY-MM-DD)
+    #[clap(short, long, conflicts_with = "today")]
+    date: Option<String>,
+    /// The ISO week to generate the report for (format: YYYY-Www)
+    #[clap(short, long, conflicts_with_all = ["today", "date"])]
+    week: Option<String>,
+    /// Compare with the 'previous' period or a specific one (format: YYYY-MM-DD or YYYY-Www)
+    #[clap(short, long)]
+    compare: Option<String>,
+    /// Group the time entries by a comma separated list of dimensions with nested subtotals
+    #[clap(
+        short,
+        long,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with = "compare"
+    )]
+    group_by: Option<Vec<Dimension>>,
+}
+
+#[derive(Parser)]
+pub(super) struct ReportTimesheetCommand {
+    /// The ISO week to generate the timesheet for (format: YYYY-Www, default: current week)
+    #[clap(short, long)]
+    week: Option<String>,
+    /// Only include tasks of this project
+    #[clap(short, long)]
+    project_name: Option<String>,
+}
+
+#[derive(Parser)]
+pub(super) struct ReportChartCommand {
+    /// First day of the chart (format: YYYY-MM-DD, default: start of the displayed weeks)
+    #[clap(short, long)]
+    from: Option<String>,
+    /// Last day of the chart (format: YYYY-MM-DD, default: today)
+    #[clap(short, long)]
+    to: Option<String>,
+    /// Number of weeks to display if no start date is given
+    #[clap(short, long, default_value_t = 12)]
+    weeks: u32,
+    /// Whether to chart the time per project or per task
+    #[clap(short, long, value_enum, default_value = "project")]
+    by: ChartGroup,
+    /// Only include time entries of this project
+    #[clap(short, long)]
+    project_name: Option<String>,
+    /// Only use ASCII characters to draw the chart
+    #[clap(short, long)]
+    ascii: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ChartGroup {
+    Project,
+    Task,
+}
+
+impl CommandExecutorTrait for ReportCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        match self {
+            ReportCommand::Project(cmd) => {
+                let window = ReportWindow::new(cmd.today, &cmd.date, &cmd.week)?;
+                if let Some(dimensions) = &cmd.group_by {
+                    return report_groups(&ctx, output, &window, None, dimensions).await;
+                }
+                let compare_window = cmd
+                    .compare
+                    .as_deref()
+                    .map(|compare| window.compare_window(compare))
+                    .transpose()?;
+                report_project(ctx, output, &window, compare_window.as_ref()).await
+            }
+            ReportCommand::Task(cmd) => {
+                let window = ReportWindow::new(cmd.today, &cmd.date, &cmd.week)?;
+                if let Some(dimensions) = &cmd.group_by {
+                    let project_name = Some(cmd.project_name.as_str());
+                    return report_groups(&ctx, output, &window, project_name, dimensions).await;
+                }
+                let compare_window = cmd
+                    .compare
+                    .as_deref()
+                    .map(|compare| window.compare_window(compare))
+                    .transpose()?;
+                report_task(
+                    ctx,
+                    output,
+                    &cmd.project_name,
+                    &window,
+                    compare_window.as_ref(),
+                )
+                .await
+            }
+            ReportCommand::Timesheet(cmd) => {
+                report_timesheet(ctx, output, &cmd.week, &cmd.project_name).await
+            }
+            ReportCommand::Chart(cmd) => report_chart(ctx, output, cmd).await,
+        }
+    }
+}
+
+/// Report the time entries grouped by a combination of dimensions with nested subtotals
+async fn report_groups(
+    ctx: &Context,
+    output: &OutputOptions,
+    window: &ReportWindow,
+    project_name: Option<&str>,
+    dimensions: &[Dimension],
+) -> miette::Result<()> {
+    let levels = aggregation::aggregate_levels(&ctx.db, window, project_name, dimensions).await?;
+    let dimension_names = dimensions.iter().map(Dimension::name).collect::<Vec<_>>();
+    let group_names = dimension_names.join(", ");
+    let scope = match project_name {
+        Some(project_name) => format!(" in project '{project_name}'"),
+        None => String::new(),
+    };
+
+    CommandOutput::builder()
+        .with_table_rows(aggregation::nested_rows(&levels, &dimension_names, output))
+        .with_prefix_message(format!(
+            "Time spent per {group_names}{scope} ({})",
+            window.title(None)
+        ))
+        .with_options(output)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+async fn report_project(
+    ctx: Context,
+    output: &OutputOptions,
+    window: &ReportWindow,
+    compare_window: Option<&ReportWindow>,
+) -> miette::Result<()> {
+    //Fetch all projects from the database
+    let all_projects = projects::Entity::find()
+        .all(&ctx.db)
+        .await
+        .map_err(|e| miette::miette!("Failed to fetch projects: {}", e))?;
+
+    let mut groups = stats_by_id(&ctx, window, None, Dimension::Project).await?;
+    let mut previous_groups = match compare_window {
+        Some(compare_window) => stats_by_id(&ctx, compare_window, None, Dimension::Project).await?,
+        None => HashMap::new(),
+    };
+    // Without a time window, the tasks without time entries are counted as well
+    let task_counts: HashMap<i32, i64> = match window.range {
+        Some(_) => HashMap::new(),
+        None => tasks::Entity::find()
+            .select_only()
+            .column(tasks::Column::ProjectId)
+            .column_as(tasks::Column::Id.count(), "tasks")
+            .group_by(tasks::Column::ProjectId)
+            .into_tuple::<(i32, i64)>()
+            .all(&ctx.db)
+            .await
+            .map_err(|e| miette::miette!("Failed to count tasks per project: {}", e))?
+            .into_iter()
+            .collect(),
+    };
+    // Rounding needs the individual time entries, so they are only loaded for the projects
+    // with a rounding policy
+    let rounded_project_ids = all_projects
+        .iter()
+        .filter(|project| ctx.config.rounding_for(&project.name).is_some())
+        .map(|project| project.id)
+        .collect::<Vec<_>>();
+    let rounded_time_entries = if rounded_project_ids.is_empty() {
+        HashMap::new()
+    } else {
+        time_entries_by(&ctx, window, rounded_project_ids, |task| task.project_id).await?
+    };
+
+    let mut project_table: Vec<ReportProjectTable> = vec![];
+    for project in all_projects {
+        let stats = groups.remove(&project.id).unwrap_or_default();
+        let time_spent_secs = stats.time_spent_secs;
+        // Apply the billing rounding policy of the project, if there is one
+        let time_rounded_secs = ctx
+            .config
+            .rounding_for(&project.name)
+            .map(|policy| policy.apply(rounded_time_entries.get(&project.id).into_iter().flatten()))
+            .unwrap_or(time_spent_secs);
+        let previous_secs = previous_groups
+            .remove(&project.id)
+            .map(|stats| stats.time_spent_secs)
+            .unwrap_or_default();
+        project_table.push(ReportProjectTable {
+            id: project.id,
+            name: project.name,
+            description: project.description.unwrap_or("".to_string()),
+            time_spent: Duration::new(time_spent_secs, output.duration_format),
+            time_rounded: Duration::new(time_rounded_secs, output.duration_format),
+            tasks: task_counts
+                .get(&project.id)
+                .map(|tasks| *tasks as usize)
+                .unwrap_or(stats.tasks.len()),
+            time_entries: stats.time_entries,
+            open_time_entries: stats.open_time_entries,
+            previous_time_spent: Duration::new(previous_secs, output.duration_format),
+            delta: Duration::new(time_spent_secs - previous_secs, output.duration_format),
+            delta_percent: Percentage::change(previous_secs, time_spent_secs),
+        });
+    }
+
+    let mut report_output = CommandOutput::builder()
+        .with_table_rows(project_table)
+        .with_prefix_message(format!(
+            "Time spent per project ({})",
+            window.title(compare_window)
+        ))
+        .with_options(output);
+    if compare_window.is_none() {
+        report_output = report_output.with_hidden_columns(&COMPARE_COLUMNS);
+    }
+    report_output.build().print()?;
+    Ok(())
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct ReportProjectTable {
+    id: i32,
+    name: String,
+    description: String,
+    time_spent: Duration,
+    time_rounded: Duration,
+    tasks: usize,
+    time_entries: usize,
+    open_time_entries: bool,
+    previous_time_spent: Duration,
+    delta: Duration,
+    delta_percent: Percentage,
+}
+
+async fn report_task(
+    ctx: Context,
+    output: &OutputOptions,
+    project_name: &str,
+    window: &ReportWindow,
+    compare_window: Option<&ReportWindow>,
+) -> miette::Result<()> {
+    // Fetch the project tasks by project name
+    let project = projects::Entity::find()
+        .filter(projects::Column::Name.eq(project_name))
+        .one(&ctx.db)
+        .await
+        .map_err(|e| miette::miette!("Failed to fetch tasks for project: {}", e))?
+        .ok_or(miette::miette!(
+            "Project with name '{}' not found",
+            project_name
+        ))?;
+    let project_tasks = project
+        .find_related(tasks::Entity)
+        .order_by_asc(tasks::Column::Id)
+        .all(&ctx.db)
+        .await
+        .map_err(|e| miette::miette!("Failed to fetch tasks for project: {}", e))?;
+    // Sum up the time entries of every task within the selected time window
+    let mut groups = stats_by_id(&ctx, window, Some(project_name), Dimension::Task).await?;
+    let mut previous_groups = match compare_window {
+        Some(compare_window) => {
+            stats_by_id(&ctx, compare_window, Some(project_name), Dimension::Task).await?
+        }
+        None => HashMap::new(),
+    };
+    let rounding = ctx.config.rounding_for(&project.name);
+    let rounded_time_entries = match rounding {
+        Some(_) => time_entries_by(&ctx, window, vec![project.id], |task| task.id).await?,
+        None => HashMap::new(),
+    };
+
+    let mut task_table: Vec<ReportTaskTable> = vec![];
+    // Tasks only worked on in the compared window are listed after the others without time spent
+    let mut previous_only: Vec<ReportTaskTable> = vec![];
+    for task in project_tasks {
+        let previous_secs = previous_groups
+            .remove(&task.id)
+            .map(|stats| stats.time_spent_secs);
+        let stats = match groups.remove(&task.id) {
+            Some(stats) => stats,
+            // Without a time window, the tasks without time entries are listed as well
+            None if window.range.is_none() => GroupStats::default(),
+            None => {
+                if let Some(previous_secs) = previous_secs {
+                    previous_only.push(ReportTaskTable {
+                        id: task.id,
+                        name: task.name,
+                        description: task.description.unwrap_or("".to_string()),
+                        time_spent: Duration::new(0, output.duration_format),
+                        time_rounded: Duration::new(0, output.duration_format),
+                        time_entries: 0,
+                        open_time_entries: false,
+                        previous_time_spent: Duration::new(previous_secs, output.duration_format),
+                        delta: Duration::new(-previous_secs, output.duration_format),
+                        delta_percent: Percentage::change(previous_secs, 0),
+                    });
+                }
+                continue;
+            }
+        };
+        let time_spent_secs = stats.time_spent_secs;
+        let time_rounded_secs = rounding
+            .map(|policy| policy.apply(rounded_time_entries.get(&task.id).into_iter().flatten()))
+            .unwrap_or(time_spent_secs);
+        let previous_secs = previous_secs.unwrap_or_default();
+        task_table.push(ReportTaskTable {
+            id: task.id,
+            name: task.name,
+            description: task.description.unwrap_or("".to_string()),
+            time_spent: Duration::new(time_spent_secs, output.duration_format),
+            time_rounded: Duration::new(time_rounded_secs, output.duration_format),
+            time_entries: stats.time_entries,
+            open_time_entries: stats.open_time_entries,
+            previous_time_spent: Duration::new(previous_secs, output.duration_format),
+            delta: Duration::new(time_spent_secs - previous_secs, output.duration_format),
+            delta_percent: Percentage::change(previous_secs, time_spent_secs),
+        });
+    }
+    task_table.extend(previous_only);
+
+    let mut report_output = CommandOutput::builder()
+        .with_table_rows(task_table)
+        .with_prefix_message(format!(
+            "Time spent per task in project '{project_name}' ({})",
+            window.title(compare_window)
+        ))
+        .with_options(output);
+    if compare_window.is_none() {
+        report_output = report_output.with_hidden_columns(&COMPARE_COLUMNS);
+    }
+    report_output.build().print()?;
+    Ok(())
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct ReportTaskTable {
+    id: i32,
+    name: String,
+    description: String,
+    time_spent: Duration,
+    time_rounded: Duration,
+    time_entries: usize,
+    open_time_entries: bool,
+    previous_time_spent: Duration,
+    delta: Duration,
+    delta_percent: Percentage,
+}
+
+/// Columns which are only shown when the report is compared with another time window
+const COMPARE_COLUMNS: [&str; 3] = ["previous_time_spent", "delta", "delta_percent"];
+
+/// Aggregate the time entries within the window per project or task, keyed by its id
+async fn stats_by_id(
+    ctx: &Context,
+    window: &ReportWindow,
+    project_name: Option<&str>,
+    dimension: Dimension,
+) -> miette::Result<HashMap<i32, GroupStats>> {
+    let groups = aggregation::aggregate(&ctx.db, window, project_name, &[dimension]).await?;
+    Ok(groups
+        .into_iter()
+        .map(|(keys, stats)| (keys[0].id(), stats))
+        .collect())
+}
+
+/// Fetch the individual time entries within the window of some projects, grouped by a key
+/// of their task
+async fn time_entries_by<K>(
+    ctx: &Context,
+    window: &ReportWindow,
+    project_ids: Vec<i32>,
+    key: impl Fn(&tasks::Model) -> K,
+) -> miette::Result<HashMap<K, Vec<time_entries::Model>>>
+where
+    K: std::hash::Hash + Eq,
+{
+    let entries_with_tasks = window
+        .filter(time_entries::Entity::find().find_also_related(tasks::Entity))
+        .filter(tasks::Column::ProjectId.is_in(project_ids))
+        .all(&ctx.db)
+        .await
+        .map_err(|e| miette::miette!("Failed to fetch time entries: {}", e))?;
+    let mut time_entries: HashMap<K, Vec<time_entries::Model>> = HashMap::new();
+    for (entry, task) in entries_with_tasks {
+        if let Some(task) = task {
+            time_entries.entry(key(&task)).or_default().push(entry);
+        }
+    }
+    Ok(time_entries)
+}
+
+/// Time window the time entries of a report are filtered by
+#[derive(Debug, Clone)]
+struct ReportWindow {
+    /// First day (inclusive) and last day (exclusive) of the window, `None` for all time
+    range: Option<(NaiveDate, NaiveDate)>,
+    label: String,
+}
+
+impl ReportWindow {
+    fn new(
+        check_today: bool,
+        date_string: &Option<String>,
+        week_string: &Option<String>,
+    ) -> miette::Result<Self> {
+        if check_today {
+            let today = chrono::Local::now().date_naive();
+            let mut window = Self::from_range(today, 1)?;
+            window.label = "today".to_string();
+            Ok(window)
+        } else if let Some(date_str) = date_string {
+            Self::from_range(parse_date(date_str)?, 1)
+        } else if let Some(week_str) = week_string {
+            Self::from_range(parse_iso_week(week_str)?, 7)
+        } else {
+            Ok(Self {
+                range: None,
+                label: "all time".to_string(),
+            })
+        }
+    }
+
+    fn from_range(start: NaiveDate, days: u64) -> miette::Result<Self> {
+        let end = start
+            .checked_add_days(chrono::Days::new(days))
+            .ok_or_else(|| miette::miette!("Failed to calculate the end of the time window"))?;
+        let label = if days == 7 && start.weekday() == Weekday::Mon {
+            start.format("%G-W%V").to_string()
+        } else if days == 1 {
+            start.to_string()
+        } else {
+            format!("{start} - {}", end.pred_opt().unwrap_or(end))
+        };
+        Ok(Self {
+            range: Some((start, end)),
+            label,
+        })
+    }
+
+    /// Window to compare with, either the `previous` window of the same length
+    /// or an explicit date (YYYY-MM-DD) or ISO week (YYYY-Www)
+    fn compare_window(&self, compare: &str) -> miette::Result<Self> {
+        let Some((start, end)) = self.range else {
+            return Err(miette::miette!(
+                "Comparing requires a time window, use --today, --date or --week"
+            ));
+        };
+        if compare == "previous" {
+            let days = (end - start).num_days() as u64;
+            let previous_start = start
+                .checked_sub_days(chrono::Days::new(days))
+                .ok_or_else(|| miette::miette!("Failed to calculate the previous time window"))?;
+            Self::from_range(previous_start, days)
+        } else if compare.contains("-W") {
+            Self::from_range(parse_iso_week(compare)?, 7)
+        } else {
+            Self::from_range(parse_date(compare)?, 1)
+        }
+    }
+
+    /// Filter the time entries by the local days of the window. The start times are stored in
+    /// UTC, so they are first narrowed down to the days around the window using their index.
+    fn filter<Q>(&self, query: Q) -> Q
+    where
+        Q: QueryFilter,
+    {
+        match self.range {
+            Some((start, end)) => query.filter(
+                time_entries::Column::StartTime
+                    .gte(start - chrono::Days::new(1))
+                    .and(time_entries::Column::StartTime.lt(end + chrono::Days::new(1)))
+                    .and(Expr::expr(aggregation::local_day()).gte(start.to_string()))
+                    .and(Expr::expr(aggregation::local_day()).lt(end.to_string())),
+            ),
+            None => query,
+        }
+    }
+
+    fn title(&self, compare_window: Option<&ReportWindow>) -> String {
+        match compare_window {
+            Some(compare_window) => format!("{} compared to {}", self.label, compare_window.label),
+            None => self.label.clone(),
+        }
+    }
+}
+
+/// Relative change between two values, displayed as a signed percentage
+#[derive(Debug, Clone, Copy)]
+struct Percentage(Option<f64>);
+
+impl Percentage {
+    fn change(previous: i64, current: i64) -> Self {
+        if previous == 0 {
+            return Self(None);
+        }
+        Self(Some((current - previous) as f64 / previous as f64 * 100.0))
+    }
+}
+
+impl std::fmt::Display for Percentage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(percentage) => write!(f, "{percentage:+.1}%"),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+impl Serialize for Percentage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0 {
+            Some(percentage) => serializer.serialize_f64((percentage * 10.0).round() / 10.0),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+async fn report_timesheet(
+    ctx: Context,
+    output: &OutputOptions,
+    week_string: &Option<String>,
+    project_name: &Option<String>,
+) -> miette::Result<()> {
+    let monday = match week_string {
+        Some(week_str) => parse_iso_week(week_str)?,
+        None => {
+            let week = chrono::Local::now().date_naive().iso_week();
+            NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon)
+                .ok_or_else(|| miette::miette!("Failed to calculate the current week"))?
+        }
+    };
+    let next_monday = monday + chrono::Days::new(7);
+
+    // Only tasks with time entries within the selected week are included
+    let groups = aggregation::aggregate(
+        &ctx.db,
+        &ReportWindow::from_range(monday, 7)?,
+        project_name.as_deref(),
+        &[Dimension::Project, Dimension::Task, Dimension::Day],
+    )
+    .await?;
+    let mut secs_per_task: BTreeMap<&[GroupKey], [i64; 7]> = BTreeMap::new();
+    for (keys, stats) in &groups {
+        let weekday = parse_date(keys[2].label())?.weekday();
+        secs_per_task.entry(&keys[..2]).or_default()[weekday.num_days_from_monday() as usize] +=
+            stats.time_spent_secs;
+    }
+
+    let mut timesheet_table: Vec<ReportTimesheetTable> = vec![];
+    let mut daily_totals = [0_i64; 7];
+    for (keys, days) in secs_per_task {
+        for (total, secs) in daily_totals.iter_mut().zip(days) {
+            *total += secs;
+        }
+        timesheet_table.push(ReportTimesheetTable::new(
+            keys[0].label(),
+            keys[1].label(),
+            days,
+            output,
+        ));
+    }
+    timesheet_table.push(ReportTimesheetTable::new("", "Total", daily_totals, output));
+
+    CommandOutput::builder()
+        .with_table_rows(timesheet_table)
+        .with_prefix_message(format!(
+            "Timesheet for week {} ({} - {})",
+            monday.format("%G-W%V"),
+            monday,
+            next_monday.pred_opt().unwrap_or(next_monday)
+        ))
+        .with_options(output)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Parse an ISO week (e.g. `2026-W41`) into the date of its monday
+fn parse_iso_week(week_str: &str) -> miette::Result<NaiveDate> {
+    let invalid_week = || miette::miette!("Invalid week format '{}', expected YYYY-Www", week_str);
+    let (year, week) = week_str.split_once("-W").ok_or_else(invalid_week)?;
+    let year = year.parse::<i32>().map_err(|_| invalid_week())?;
+    let week = week.parse::<u32>().map_err(|_| invalid_week())?;
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+        .ok_or_else(|| miette::miette!("Week {} does not exist in {}", week, year))
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct ReportTimesheetTable {
+    project: String,
+    task: String,
+    mon: Duration,
+    tue: Duration,
+    wed: Duration,
+    thu: Duration,
+    fri: Duration,
+    sat: Duration,
+    sun: Duration,
+    total: Duration,
+}
+
+impl ReportTimesheetTable {
+    fn new(project: &str, task: &str, days: [i64; 7], output: &OutputOptions) -> Self {
+        let day = |index: usize| Duration::new(days[index], output.duration_format);
+        Self {
+            project: project.to_string(),
+            task: task.to_string(),
+            mon: day(0),
+            tue: day(1),
+            wed: day(2),
+            thu: day(3),
+            fri: day(4),
+            sat: day(5),
+            sun: day(6),
+            total: Duration::new(days.iter().sum(), output.duration_format),
+        }
+    }
+}
+
+async fn report_chart(
+    ctx: Context,
+    output: &OutputOptions,
+    cmd: &ReportChartCommand,
+) -> miette::Result<()> {
+    let to = match &cmd.to {
+        Some(date_str) => parse_date(date_str)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let from = match &cmd.from {
+        Some(date_str) => parse_date(date_str)?,
+        None => {
+            let monday = to - chrono::Days::new(u64::from(to.weekday().num_days_from_monday()));
+            monday - chrono::Days::new(u64::from(cmd.weeks.max(1) - 1) * 7)
+        }
+    };
+    if from > to {
+        return Err(miette::miette!(
+            "The start date {} is after the end date {}",
+            from,
+            to
+        ));
+    }
+    let days = (to - from).num_days() as u64 + 1;
+
+    // Aggregate the tracked time per day and per project or task
+    let dimensions = match cmd.by {
+        ChartGroup::Project => vec![Dimension::Project, Dimension::Day],
+        ChartGroup::Task => vec![Dimension::Project, Dimension::Task, Dimension::Day],
+    };
+    let groups = aggregation::aggregate(
+        &ctx.db,
+        &ReportWindow::from_range(from, days)?,
+        cmd.project_name.as_deref(),
+        &dimensions,
+    )
+    .await?;
+    let mut secs_per_day: HashMap<NaiveDate, i64> = HashMap::new();
+    let mut secs_per_group: BTreeMap<String, i64> = BTreeMap::new();
+    for (keys, stats) in &groups {
+        let (day, group_keys) = keys.split_last().expect("The day is always a dimension");
+        *secs_per_day.entry(parse_date(day.label())?).or_default() += stats.time_spent_secs;
+        let group = group_keys
+            .iter()
+            .map(GroupKey::label)
+            .collect::<Vec<_>>()
+            .join("/");
+        *secs_per_group.entry(group).or_default() += stats.time_spent_secs;
+    }
+
+    let title = format!("Tracked time from {from} to {to}");
+    let group_title = match cmd.by {
+        ChartGroup::Project => "Time per project",
+        ChartGroup::Task => "Time per task",
+    };
+
+    // Machine readable formats get the aggregated values instead of the drawing
+    if output.format != OutputFormat::Human {
+        let mut chart_table: Vec<ReportChartTable> = from
+            .iter_days()
+            .take_while(|day| *day <= to)
+            .map(|day| ReportChartTable {
+                group: "day".to_string(),
+                label: day.to_string(),
+                time_spent: Duration::new(
+                    secs_per_day.get(&day).copied().unwrap_or_default(),
+                    output.duration_format,
+                ),
+            })
+            .collect();
+        chart_table.extend(secs_per_group.iter().map(|(label, secs)| ReportChartTable {
+            group: format!("{:?}", cmd.by).to_lowercase(),
+            label: label.clone(),
+            time_spent: Duration::new(*secs, output.duration_format),
+        }));
+        CommandOutput::builder()
+            .with_table_rows(chart_table)
+            .with_prefix_message(title)
+            .with_options(output)
+            .build()
+            .print()?;
+        return Ok(());
+    }
+
+    let style = ChartStyle::detect(cmd.ascii);
+    let mut chart_output = CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(title);
+    for line in chart::heatmap(&style, from, to, |day| {
+        secs_per_day.get(&day).copied().unwrap_or_default()
+    }) {
+        chart_output = chart_output.with_prefix_message(line);
+    }
+    chart_output = chart_output
+        .with_suffix_message(String::new())
+        .with_suffix_message(group_title.to_string());
+    let group_values = secs_per_group.into_iter().collect::<Vec<_>>();
+    if group_values.is_empty() {
+        chart_output = chart_output.with_suffix_message("No time tracked".to_string());
+    }
+    for line in chart::bar_chart(&style, output.duration_format, &group_values) {
+        chart_output = chart_output.with_suffix_message(line);
+    }
+    chart_output.build().print()?;
+    Ok(())
+}
+
+fn parse_date(date_str: &str) -> miette::Result<NaiveDate> {
+    NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+        .map_err(|e| miette::miette!("Invalid date format: {}", e))
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct ReportChartTable {
+    group: String,
+    label: String,
+    time_spent: Duration,
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use super::{
+    ReportWindow,
+    aggregation::{self, Dimension, GroupKey, GroupStats},
+};
+use crate::{
+    Context,
+    commands::{CommandExecutorTrait, OutputOptions, command_output::CommandOutput, sync},
+    db, team,
+};
+
+#[derive(Subcommand)]
+pub(in crate::commands) enum TeamCommand {
+    /// Report the combined time spent per teammate and project
+    Report(TeamReportCommand),
+}
+
+#[derive(Parser)]
+pub(in crate::commands) struct TeamReportCommand {
+    /// Whether to generate the report for today
+    #[clap(short, long)]
+    today: bool,
+    /// The date to generate the report for (format: YYYY-MM-DD)
+    #[clap(short, long, conflicts_with = "today")]
+    date: Option<String>,
+    /// The ISO week to generate the report for (format: YYYY-Www)
+    #[clap(short, long, conflicts_with_all = ["today", "date"])]
+    week: Option<String>,
+    /// Whether to group the time per teammate or per project first
+    #[clap(short, long, value_enum, default_value = "member")]
+    by: TeamGroup,
+    /// Import the latest databases of the teammates in the config from the sync storage first
+    #[clap(short, long)]
+    fetch: bool,
+    /// Additional database files to include, named after the file
+    #[clap(long)]
+    file: Vec<PathBuf>,
+    /// Leave out the local database
+    #[clap(long)]
+    exclude_local: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TeamGroup {
+    Member,
+    Project,
+}
+
+impl CommandExecutorTrait for TeamCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        match self {
+            TeamCommand::Report(cmd) => report_team(ctx, output, cmd).await,
+        }
+    }
+}
+
+/// Report the time spent per teammate and project across the local database, the imported
+/// databases of the teammates and additional database files. The databases of the teammates
+/// are only read, nothing is merged into the local database.
+async fn report_team(
+    ctx: Context,
+    output: &OutputOptions,
+    cmd: &TeamReportCommand,
+) -> miette::Result<()> {
+    let window = ReportWindow::new(cmd.today, &cmd.date, &cmd.week)?;
+    if cmd.fetch {
+        if ctx.config.team.members.is_empty() {
+            return Err(miette::miette!(
+                help = "List the sync prefixes of your teammates as `members` in the [team] section of the config file",
+                "No teammates configured to fetch"
+            ));
+        }
+        sync::import_team_members(&ctx.config, &ctx.config.team.members).await?;
+    }
+
+    let db_path = db::db_path(&ctx.config)?;
+    let mut databases = team::imported_members(&db_path).await?;
+    for path in &cmd.file {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        databases.push((name, path.clone()));
+    }
+    let local_name = (!cmd.exclude_local).then(|| team::local_member_name(&ctx.config));
+    // The time of the databases is combined by their names, so the names have to be unique
+    let mut names = BTreeSet::new();
+    for name in local_name
+        .iter()
+        .chain(databases.iter().map(|(name, _)| name))
+    {
+        if !names.insert(name) {
+            return Err(miette::miette!(
+                help = "Rename the files given with --file, or leave out the local database with --exclude-local",
+                "The team report contains more than one database named '{}'",
+                name
+            ));
+        }
+    }
+    if databases.is_empty() && cmd.exclude_local {
+        return Err(miette::miette!(
+            help = "Import the database of a teammate with `clog sync pull --from <prefix>`",
+            "No databases to report on"
+        ));
+    }
+
+    let mut groups = BTreeMap::new();
+    if let Some(name) = &local_name {
+        let member_groups =
+            aggregation::aggregate(&ctx.db, &window, None, &[Dimension::Project]).await?;
+        add_member_groups(&mut groups, name, member_groups, cmd.by);
+    }
+    for (name, path) in &databases {
+        let member_db = db::open_read_only(path).await?;
+        let member_groups =
+            aggregation::aggregate(&member_db, &window, None, &[Dimension::Project]).await?;
+        member_db
+            .close()
+            .await
+            .map_err(|e| miette::miette!("Failed to close database {}: {}", path.display(), e))?;
+        add_member_groups(&mut groups, name, member_groups, cmd.by);
+    }
+
+    let dimension_names = match cmd.by {
+        TeamGroup::Member => ["member", "project"],
+        TeamGroup::Project => ["project", "member"],
+    };
+    let levels = aggregation::sum_levels(groups, dimension_names.len());
+    CommandOutput::builder()
+        .with_table_rows(aggregation::nested_rows(&levels, &dimension_names, output))
+        .with_prefix_message(format!(
+            "Time spent per {} ({})",
+            dimension_names.join(", "),
+            window.title(None)
+        ))
+        .with_options(output)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Add the time per project of a teammate to the groups of the team report. Projects are
+/// identified by their name, as the ids differ between the databases.
+fn add_member_groups(
+    groups: &mut BTreeMap<Vec<GroupKey>, GroupStats>,
+    member: &str,
+    member_groups: BTreeMap<Vec<GroupKey>, GroupStats>,
+    by: TeamGroup,
+) {
+    let member_key = GroupKey::new(member, 0);
+    for (keys, stats) in member_groups {
+        let project_key = keys[0].by_label();
+        let keys = match by {
+            TeamGroup::Member => vec![member_key.clone(), project_key],
+            TeamGroup::Project => vec![project_key, member_key.clone()],
+        };
+        groups.entry(keys).or_default().add(&stats);
+    }
+}
@@ -0,0 +1,59 @@
+use clap::Parser;
+use sea_orm::prelude::*;
+
+use crate::{
+    Context,
+    commands::{
+        CommandExecutorTrait, OutputOptions,
+        command_output::CommandOutput,
+        goal::{GoalTable, goal_progress, tracked_secs},
+    },
+    duration::Duration,
+    entity::{projects, tasks, time_entries},
+};
+
+#[derive(Parser)]
+pub(super) struct StatusCommand;
+
+impl CommandExecutorTrait for StatusCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        let active_entry = time_entries::Entity::find()
+            .filter(time_entries::Column::EndTime.is_null())
+            .find_also_related(tasks::Entity)
+            .one(&ctx.db)
+            .await
+            .map_err(|e| miette::miette!("Failed to find active time entry: {}", e))?;
+
+        let active_message = match active_entry {
+            Some((entry, Some(task))) => {
+                let project = task
+                    .find_related(projects::Entity)
+                    .one(&ctx.db)
+                    .await
+                    .map_err(|e| miette::miette!("Failed to find project: {}", e))?
+                    .ok_or_else(|| miette::miette!("Project not found"))?;
+                format!(
+                    "Working on task '{}' in project '{}' since {} ({})",
+                    task.name,
+                    project.name,
+                    entry
+                        .start_time
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M"),
+                    Duration::new(tracked_secs(&entry), output.duration_format)
+                )
+            }
+            _ => "No task in progress".to_string(),
+        };
+
+        let goal_table = goal_progress(&ctx, output, None).await?;
+        let mut status_output = CommandOutput::<Vec<GoalTable>, GoalTable>::builder()
+            .with_options(output)
+            .with_prefix_message(active_message);
+        if !goal_table.is_empty() {
+            status_output = status_output.with_table_rows(goal_table);
+        }
+        status_output.build().print()?;
+        Ok(())
+    }
+}
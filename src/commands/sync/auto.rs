@@ -0,0 +1,200 @@
+use std::{future::Future, path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    REMOTE_FILE_NAME, backend::SyncBackend, encryption::EncryptionSecret, fetch,
+    read_database_file, replace_local, state::SyncState, upload,
+};
+use crate::{
+    config::Config,
+    db::{self, sidecar_path},
+};
+
+const AUTO_SYNC_SUFFIX: &str = ".auto-sync.json";
+
+/// State of the automatic sync, stored in a sidecar file next to the database
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AutoSyncState {
+    /// When the remote storage was last contacted, successfully or not
+    last_attempt: Option<DateTime<Utc>>,
+    /// Whether local changes are queued, because they could not be pushed yet
+    pending: bool,
+}
+
+impl AutoSyncState {
+    fn load(db_path: &Path) -> miette::Result<Self> {
+        let path = sidecar_path(db_path, AUTO_SYNC_SUFFIX);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            miette::miette!(
+                "Failed to read auto-sync state file {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| miette::miette!("Invalid auto-sync state file {}: {}", path.display(), e))
+    }
+
+    fn save(&self, db_path: &Path) -> miette::Result<()> {
+        let path = sidecar_path(db_path, AUTO_SYNC_SUFFIX);
+        let state = serde_json::to_string_pretty(self)
+            .map_err(|e| miette::miette!("Failed to serialize auto-sync state: {}", e))?;
+        std::fs::write(&path, state).map_err(|e| {
+            miette::miette!(
+                "Failed to write auto-sync state file {}: {}",
+                path.display(),
+                e
+            )
+        })
+    }
+
+    /// Whether the interval since the last contact with the remote storage has passed
+    fn due(&self, config: &Config) -> bool {
+        let interval = chrono::Duration::seconds(config.sync.auto.interval_secs as i64);
+        self.last_attempt
+            .is_none_or(|last_attempt| Utc::now() - last_attempt >= interval)
+    }
+}
+
+/// Run before a command: push the queued local changes, or pull the remote database if `pull`
+/// is set for a command which changes the local one, if it is newer and the local one is
+/// unchanged.
+/// The remote storage is contacted at most once per `sync.auto.interval_secs`, and failures
+/// are only reported as warnings.
+/// Returns the connection to the local database, which is reopened after a pull.
+pub(in crate::commands) async fn auto_sync_before(
+    db: DatabaseConnection,
+    config: &Config,
+    pull: bool,
+) -> miette::Result<DatabaseConnection> {
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let mut state = AutoSyncState::load(&db_path)?;
+    if !(pull || state.pending) || !state.due(config) {
+        return Ok(db);
+    }
+    state.last_attempt = Some(Utc::now());
+
+    let db = if state.pending {
+        match push_local_changes(&db, config).await {
+            Ok(()) => state.pending = false,
+            Err(e) => warn_queued(&e),
+        }
+        db
+    } else {
+        match pull_remote_changes(db.clone(), config).await {
+            Ok(false) => db,
+            // The database was replaced, or the pull failed after closing it
+            result => {
+                if let Err(e) = result {
+                    eprintln!("Warning: Auto-sync failed to pull the remote changes: {e}");
+                }
+                db::connect(config).await?
+            }
+        }
+    };
+    state.save(&db_path)?;
+    Ok(db)
+}
+
+/// Run after a command which changed the local database: push the changes, or queue them
+/// if the remote storage can't be reached. Queued changes are retried once
+/// `sync.auto.interval_secs` passed since the last attempt.
+pub(in crate::commands) async fn auto_sync_after(config: &Config) -> miette::Result<()> {
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let mut state = AutoSyncState::load(&db_path)?;
+    if state.pending && !state.due(config) {
+        return Ok(());
+    }
+    state.last_attempt = Some(Utc::now());
+
+    let db = db::connect(config).await?;
+    match push_local_changes(&db, config).await {
+        Ok(()) => state.pending = false,
+        Err(e) => {
+            state.pending = true;
+            warn_queued(&e);
+        }
+    }
+    db.close()
+        .await
+        .map_err(|e| miette::miette!("Failed to close the database: {}", e))?;
+    state.save(&db_path)
+}
+
+/// Push the local database, unless it is unchanged since the last sync
+async fn push_local_changes(db: &DatabaseConnection, config: &Config) -> miette::Result<()> {
+    let backend = with_timeout(config, SyncBackend::from_config(config)).await?;
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    if let Some(sync_state) = SyncState::load(&db_path, backend.url())?
+        && !sync_state.local_changed(&read_database_file(&db_path).await?)
+    {
+        return Ok(());
+    }
+    let secret = EncryptionSecret::from_config(config).await?;
+    with_timeout(config, upload(db, config, &backend, secret.as_ref(), false)).await
+}
+
+/// Pull the remote database if it changed since the last sync and the local one didn't,
+/// returning whether the local database was replaced. Databases which were never synced
+/// are left to `clog sync`.
+async fn pull_remote_changes(db: DatabaseConnection, config: &Config) -> miette::Result<bool> {
+    let backend = with_timeout(config, SyncBackend::from_config(config)).await?;
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let Some(sync_state) = SyncState::load(&db_path, backend.url())? else {
+        return Ok(false);
+    };
+    if sync_state.local_changed(&read_database_file(&db_path).await?) {
+        return Ok(false);
+    }
+    let remote_file_path = backend.path(REMOTE_FILE_NAME);
+    let remote_meta = with_timeout(config, async {
+        backend
+            .store()
+            .head(&remote_file_path)
+            .await
+            .map_err(|e| miette::miette!("Failed to read metadata from remote storage: {}", e))
+    })
+    .await?;
+    if sync_state.matches_remote(&remote_meta) {
+        return Ok(false);
+    }
+
+    let secret = EncryptionSecret::from_config(config).await?;
+    let (remote_meta, file_bytes) =
+        with_timeout(config, fetch(&backend, secret.as_ref(), None)).await?;
+    replace_local(db, config, &backend, None, remote_meta, &file_bytes, false).await?;
+    Ok(true)
+}
+
+/// Give up on the remote storage after `sync.auto.timeout_secs`, so commands don't hang
+/// while offline
+async fn with_timeout<T>(
+    config: &Config,
+    future: impl Future<Output = miette::Result<T>>,
+) -> miette::Result<T> {
+    let timeout_secs = config.sync.auto.timeout_secs;
+    tokio::time::timeout(Duration::from_secs(timeout_secs), future)
+        .await
+        .map_err(|_| {
+            miette::miette!(
+                "The remote storage did not respond within {} seconds",
+                timeout_secs
+            )
+        })?
+}
+
+fn warn_queued(error: &miette::Report) {
+    eprintln!(
+        "Warning: Auto-sync failed to push the local changes: {error}\nThey are queued until the next attempt, or merged with the remote changes by `clog sync`."
+    );
+}
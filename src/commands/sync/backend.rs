@@ -0,0 +1,268 @@
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+use object_store::{
+    ObjectStore, ObjectStoreScheme, PutMode, PutPayload, PutResult, aws::AmazonS3Builder,
+    azure::MicrosoftAzureBuilder, gcp::GoogleCloudStorageBuilder, local::LocalFileSystem,
+    memory::InMemory, path::Path,
+};
+use url::Url;
+
+use super::credentials::{self, SecretName};
+use crate::config::Config;
+
+/// Remote storage the database is synced with
+pub(super) struct SyncBackend {
+    store: Arc<dyn ObjectStore>,
+    /// Path within the store the URL points to
+    root: Path,
+    /// Path within the store all remote files are placed under,
+    /// the root or the configured prefix of the user below it
+    prefix: Path,
+    root_url: String,
+    url: String,
+}
+
+impl SyncBackend {
+    /// Set up the backend configured by the `sync.url` setting, falling back to the Azure
+    /// storage account of the legacy `CLOG_STORAGE_*` environment variables
+    /// The remote files are placed below the `sync.prefix` setting, so a team can share
+    /// the same storage.
+    /// The credentials of the storage are looked up like the other sync secrets, unless they
+    /// are set as options.
+    pub(super) async fn from_config(config: &Config) -> miette::Result<Self> {
+        let url = if let Some(url) = &config.sync.url {
+            url.clone()
+        } else if std::env::var("CLOG_STORAGE_ACCOUNT_NAME").is_ok() {
+            let container_name = std::env::var("CLOG_CONTAINER_NAME").unwrap_or("sync".to_string());
+            format!("az://{container_name}")
+        } else {
+            return Err(miette::miette!(
+                help = "Set it with `clog config set sync.url <url>`, the CLOG_SYNC_URL environment variable or `--set sync.url=<url>`",
+                "No sync backend configured"
+            ));
+        };
+        let mut options = config.sync.options.clone();
+        for secret in SecretName::for_scheme(parse_scheme(&url)?) {
+            if options.contains_key(secret.name()) {
+                continue;
+            }
+            if let Some((value, _)) = credentials::resolve(config, *secret).await? {
+                options.insert(secret.name().to_string(), value);
+            }
+        }
+        let backend = Self::from_url(&url, &options)?;
+        match &config.sync.prefix {
+            Some(prefix) => backend.with_prefix(prefix),
+            None => Ok(backend),
+        }
+    }
+
+    /// Backend for the remote files below a prefix like `alice` or `alice/laptop`,
+    /// relative to the URL of the storage
+    pub(super) fn with_prefix(&self, prefix: &str) -> miette::Result<Self> {
+        let prefix_path = Path::parse(prefix.trim_matches('/'))
+            .ok()
+            .filter(|prefix_path| prefix_path.parts().count() > 0)
+            .ok_or_else(|| miette::miette!("Invalid sync prefix '{}'", prefix))?;
+        Ok(Self {
+            store: Arc::clone(&self.store),
+            root: self.root.clone(),
+            prefix: self.root.parts().chain(prefix_path.parts()).collect(),
+            root_url: self.root_url.clone(),
+            url: format!("{}/{}", self.root_url.trim_end_matches('/'), prefix_path),
+        })
+    }
+
+    /// Set up the backend for a URL like `s3://bucket/path`, `gs://bucket/path`,
+    /// `az://container/path`, `file:///path/to/dir` or `memory:///`.
+    /// The options configure the client of the cloud storages, on top of the environment.
+    pub(super) fn from_url(url: &str, options: &BTreeMap<String, String>) -> miette::Result<Self> {
+        let parsed_url = parse_url(url)?;
+        let (scheme, prefix) = ObjectStoreScheme::parse(&parsed_url)
+            .map_err(|e| miette::miette!("Unsupported sync URL '{}': {}", url, e))?;
+        if !options.is_empty()
+            && matches!(scheme, ObjectStoreScheme::Local | ObjectStoreScheme::Memory)
+        {
+            return Err(miette::miette!(
+                help = "Remove the [sync.options] section of the config file",
+                "Sync options are not supported for '{}'",
+                url
+            ));
+        }
+
+        let store: Box<dyn ObjectStore> = match scheme {
+            ObjectStoreScheme::Local => {
+                let directory = parsed_url
+                    .to_file_path()
+                    .map_err(|_| miette::miette!("Invalid local sync directory '{}'", url))?;
+                std::fs::create_dir_all(&directory).map_err(|e| {
+                    miette::miette!(
+                        "Failed to create sync directory {}: {}",
+                        directory.display(),
+                        e
+                    )
+                })?;
+                let local = LocalFileSystem::new_with_prefix(&directory)
+                    .map_err(|e| miette::miette!("Failed to set up local sync directory: {}", e))?;
+                // The directory is the root of the store, so there is no prefix within it
+                return Ok(Self {
+                    store: Arc::new(local),
+                    root: Path::default(),
+                    prefix: Path::default(),
+                    root_url: url.to_string(),
+                    url: url.to_string(),
+                });
+            }
+            ObjectStoreScheme::Memory => Box::new(InMemory::new()),
+            ObjectStoreScheme::AmazonS3 => {
+                let mut s3_builder = AmazonS3Builder::from_env().with_url(url);
+                for (key, value) in options {
+                    s3_builder = s3_builder.with_config(parse_option(key, "S3")?, value);
+                }
+                Box::new(
+                    s3_builder
+                        .build()
+                        .map_err(|e| miette::miette!("Failed to set up S3 client: {}", e))?,
+                )
+            }
+            ObjectStoreScheme::GoogleCloudStorage => {
+                let mut gcs_builder = GoogleCloudStorageBuilder::from_env().with_url(url);
+                for (key, value) in options {
+                    gcs_builder =
+                        gcs_builder.with_config(parse_option(key, "Google Cloud Storage")?, value);
+                }
+                Box::new(gcs_builder.build().map_err(|e| {
+                    miette::miette!("Failed to set up Google Cloud Storage client: {}", e)
+                })?)
+            }
+            ObjectStoreScheme::MicrosoftAzure => {
+                let mut azure_builder = MicrosoftAzureBuilder::from_env().with_url(url);
+                if let Ok(account_name) = std::env::var("CLOG_STORAGE_ACCOUNT_NAME") {
+                    azure_builder = azure_builder.with_account(account_name);
+                }
+                for (key, value) in options {
+                    azure_builder =
+                        azure_builder.with_config(parse_option(key, "Azure Blob Storage")?, value);
+                }
+                Box::new(
+                    azure_builder.build().map_err(|e| {
+                        miette::miette!("Failed to set up Azure Blob client: {}", e)
+                    })?,
+                )
+            }
+            _ => {
+                return Err(miette::miette!(
+                    "Unsupported sync URL '{}', expected one of s3://, gs://, az://, file:// or memory://",
+                    url
+                ));
+            }
+        };
+        Ok(Self {
+            store: store.into(),
+            root: prefix.clone(),
+            prefix,
+            root_url: url.to_string(),
+            url: url.to_string(),
+        })
+    }
+
+    /// URL of the remote files, including the prefix
+    pub(super) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(super) fn store(&self) -> &dyn ObjectStore {
+        self.store.as_ref()
+    }
+
+    /// Path of a remote file within the backend
+    pub(super) fn path(&self, file_name: &str) -> Path {
+        self.prefix.child(file_name)
+    }
+
+    /// Upload a file unless the condition of the put mode fails because the remote file
+    /// was created or changed in the meantime.
+    /// Backends without conditional puts, like local directories, check the condition
+    /// before uploading instead.
+    pub(super) async fn put_conditional(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        mode: PutMode,
+    ) -> miette::Result<PutOutcome> {
+        let upload_error = |e: object_store::Error| {
+            miette::miette!(
+                "Failed to upload file to remote storage at {}: {}",
+                location,
+                e
+            )
+        };
+        match self
+            .store
+            .put_opts(location, payload.clone(), mode.clone().into())
+            .await
+        {
+            Ok(result) => return Ok(PutOutcome::Stored(result)),
+            Err(object_store::Error::Precondition { .. })
+            | Err(object_store::Error::AlreadyExists { .. }) => return Ok(PutOutcome::Conflict),
+            Err(object_store::Error::NotImplemented) => {}
+            Err(e) => return Err(upload_error(e)),
+        }
+
+        let current = match self.store.head(location).await {
+            Ok(meta) => Some(meta),
+            Err(object_store::Error::NotFound { .. }) => None,
+            Err(e) => return Err(upload_error(e)),
+        };
+        let condition_holds = match (&mode, &current) {
+            (PutMode::Overwrite, _) => true,
+            (PutMode::Create, current) => current.is_none(),
+            (PutMode::Update(expected), Some(current)) => {
+                expected.e_tag == current.e_tag && expected.version == current.version
+            }
+            (PutMode::Update(_), None) => false,
+        };
+        if !condition_holds {
+            return Ok(PutOutcome::Conflict);
+        }
+        self.store
+            .put_opts(location, payload, PutMode::Overwrite.into())
+            .await
+            .map(PutOutcome::Stored)
+            .map_err(upload_error)
+    }
+}
+
+/// Outcome of a conditional upload
+pub(super) enum PutOutcome {
+    Stored(PutResult),
+    /// The remote file doesn't match the expected version
+    Conflict,
+}
+
+fn parse_url(url: &str) -> miette::Result<Url> {
+    Url::parse(url).map_err(|e| miette::miette!("Invalid sync URL '{}': {}", url, e))
+}
+
+fn parse_scheme(url: &str) -> miette::Result<ObjectStoreScheme> {
+    ObjectStoreScheme::parse(&parse_url(url)?)
+        .map(|(scheme, _)| scheme)
+        .map_err(|e| miette::miette!("Unsupported sync URL '{}': {}", url, e))
+}
+
+/// Parse the name of an option of a storage client, like `aws_region` or `region` for S3
+fn parse_option<K>(key: &str, storage: &str) -> miette::Result<K>
+where
+    K: FromStr<Err = object_store::Error>,
+{
+    key.parse().map_err(|e| {
+        miette::miette!(
+            help =
+                "See the documentation of the object_store crate for the options of each storage",
+            "Invalid sync option '{}' for {}: {}",
+            key,
+            storage,
+            e
+        )
+    })
+}
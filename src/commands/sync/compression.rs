@@ -0,0 +1,59 @@
+/// Marks a packed remote database, followed by the header version and the format
+const MAGIC: &[u8] = b"CLOGDB";
+const VERSION: u8 = 1;
+const HEADER_LENGTH: usize = MAGIC.len() + 2;
+
+/// Compression of the remote database, stored in its header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Zstd = 1,
+}
+
+impl Format {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Format::Zstd),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn is_compressed(content: &[u8]) -> bool {
+    content.starts_with(MAGIC)
+}
+
+/// Compress a database for the upload and prepend the header
+pub(super) fn compress(content: &[u8]) -> miette::Result<Vec<u8>> {
+    let mut packed = Vec::with_capacity(HEADER_LENGTH + content.len() / 4);
+    packed.extend_from_slice(MAGIC);
+    packed.push(VERSION);
+    packed.push(Format::Zstd as u8);
+    zstd::stream::copy_encode(content, &mut packed, zstd::DEFAULT_COMPRESSION_LEVEL)
+        .map_err(|e| miette::miette!("Failed to compress the database: {}", e))?;
+    Ok(packed)
+}
+
+/// Decompress a downloaded database. Databases without the header were uploaded
+/// uncompressed by older versions of clog and are returned as they are.
+pub(super) fn decompress(content: &[u8]) -> miette::Result<Vec<u8>> {
+    if !is_compressed(content) {
+        return Ok(content.to_vec());
+    }
+    if content.len() < HEADER_LENGTH {
+        return Err(miette::miette!(
+            "The compressed remote database is truncated"
+        ));
+    }
+    let version = content[MAGIC.len()];
+    let format = Format::from_byte(content[MAGIC.len() + 1]);
+    let Some(Format::Zstd) = format.filter(|_| version == VERSION) else {
+        return Err(miette::miette!(
+            help = "Update clog to sync with this database",
+            "The remote database is compressed with an unknown format (version {}, format {})",
+            version,
+            content[MAGIC.len() + 1]
+        ));
+    };
+    zstd::stream::decode_all(&content[HEADER_LENGTH..])
+        .map_err(|e| miette::miette!("Failed to decompress the remote database: {}", e))
+}
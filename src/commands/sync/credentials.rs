@@ -0,0 +1,210 @@
+use std::process::Stdio;
+
+use clap::ValueEnum;
+use object_store::ObjectStoreScheme;
+
+use crate::config::Config;
+
+/// Service the secrets of clog are stored under in the OS keyring
+const KEYRING_SERVICE: &str = "clog";
+
+/// Secret needed by the sync, which doesn't have to be kept in the environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "snake_case")]
+pub(super) enum SecretName {
+    /// Passphrase the key of the encrypted remote database is derived from
+    Passphrase,
+    /// Access key ID of S3
+    AwsAccessKeyId,
+    /// Secret access key of S3
+    AwsSecretAccessKey,
+    /// Service account key of Google Cloud Storage, in JSON
+    GoogleServiceAccountKey,
+    /// Access key of the Azure storage account
+    AzureStorageAccountKey,
+}
+
+impl SecretName {
+    /// Name of the secret for the credential helper and the keyring, which is also
+    /// the name of the option of the storage client for the storage credentials
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            SecretName::Passphrase => "passphrase",
+            SecretName::AwsAccessKeyId => "aws_access_key_id",
+            SecretName::AwsSecretAccessKey => "aws_secret_access_key",
+            SecretName::GoogleServiceAccountKey => "google_service_account_key",
+            SecretName::AzureStorageAccountKey => "azure_storage_account_key",
+        }
+    }
+
+    /// Environment variables the secret falls back to
+    pub(super) fn env_vars(&self) -> &'static [&'static str] {
+        match self {
+            SecretName::Passphrase => &["CLOG_SYNC_PASSPHRASE"],
+            SecretName::AwsAccessKeyId => &["AWS_ACCESS_KEY_ID"],
+            SecretName::AwsSecretAccessKey => &["AWS_SECRET_ACCESS_KEY"],
+            SecretName::GoogleServiceAccountKey => &["GOOGLE_SERVICE_ACCOUNT_KEY"],
+            SecretName::AzureStorageAccountKey => &[
+                "CLOG_STORAGE_ACCESS_KEY",
+                "AZURE_STORAGE_ACCOUNT_KEY",
+                "AZURE_STORAGE_ACCESS_KEY",
+            ],
+        }
+    }
+
+    /// Credentials of the storage client of a URL scheme
+    pub(super) fn for_scheme(scheme: ObjectStoreScheme) -> &'static [SecretName] {
+        match scheme {
+            ObjectStoreScheme::AmazonS3 => {
+                &[SecretName::AwsAccessKeyId, SecretName::AwsSecretAccessKey]
+            }
+            ObjectStoreScheme::GoogleCloudStorage => &[SecretName::GoogleServiceAccountKey],
+            ObjectStoreScheme::MicrosoftAzure => &[SecretName::AzureStorageAccountKey],
+            _ => &[],
+        }
+    }
+}
+
+/// Where a secret was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SecretSource {
+    Helper,
+    Keyring,
+    Env(&'static str),
+}
+
+impl std::fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretSource::Helper => write!(f, "credential helper"),
+            SecretSource::Keyring => write!(f, "keyring"),
+            SecretSource::Env(variable) => write!(f, "env {variable}"),
+        }
+    }
+}
+
+/// Look up a secret from the credential helper of the `sync.credential_helper` setting,
+/// then the OS keyring if `sync.keyring` is enabled, falling back to the environment
+pub(super) async fn resolve(
+    config: &Config,
+    secret: SecretName,
+) -> miette::Result<Option<(String, SecretSource)>> {
+    if let Some(helper) = &config.sync.credential_helper
+        && let Some(value) = from_helper(helper, secret).await?
+    {
+        return Ok(Some((value, SecretSource::Helper)));
+    }
+    if config.sync.keyring
+        && let Some(value) = from_keyring(secret).await?
+    {
+        return Ok(Some((value, SecretSource::Keyring)));
+    }
+    Ok(secret.env_vars().iter().find_map(|variable| {
+        std::env::var(variable)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| (value, SecretSource::Env(variable)))
+    }))
+}
+
+/// Run the credential helper like `<helper> get <name>` through the shell, like git does.
+/// The helper prints the secret, or nothing if it doesn't know it.
+async fn from_helper(helper: &str, secret: SecretName) -> miette::Result<Option<String>> {
+    let output = shell_command(helper)
+        .args(["get", secret.name()])
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .await
+        .map_err(|e| miette::miette!("Failed to run credential helper '{}': {}", helper, e))?;
+    if !output.status.success() {
+        return Err(miette::miette!(
+            help = "Check the `sync.credential_helper` setting",
+            "Credential helper '{}' failed to get '{}' ({})",
+            helper,
+            secret.name(),
+            output.status
+        ));
+    }
+    let value = String::from_utf8(output.stdout).map_err(|_| {
+        miette::miette!(
+            "Credential helper '{}' returned an invalid secret for '{}'",
+            helper,
+            secret.name()
+        )
+    })?;
+    let value = value.trim_end_matches(['\r', '\n']);
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+#[cfg(unix)]
+fn shell_command(helper: &str) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new("sh");
+    // The arguments are passed to the helper as "$@", so they are never interpreted by the shell
+    command.args(["-c", &format!("{helper} \"$@\""), helper]);
+    command
+}
+
+#[cfg(not(unix))]
+fn shell_command(helper: &str) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new("cmd");
+    command.args(["/C", helper]);
+    command
+}
+
+/// Read a secret from the OS keyring. A keyring which isn't available, e.g. without a
+/// secret service on headless machines, is treated like a keyring without the secret.
+async fn from_keyring(secret: SecretName) -> miette::Result<Option<String>> {
+    // The keyring blocks on its own runtime, which can't run within the runtime of clog
+    let result = tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, secret.name())?.get_password()
+    })
+    .await
+    .map_err(|e| miette::miette!("Failed to read from the keyring: {}", e))?;
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry)
+        | Err(keyring::Error::PlatformFailure(_))
+        | Err(keyring::Error::NoStorageAccess(_)) => Ok(None),
+        Err(e) => Err(miette::miette!(
+            "Failed to read '{}' from the keyring: {}",
+            secret.name(),
+            e
+        )),
+    }
+}
+
+/// Store a secret in the OS keyring
+pub(super) async fn store_in_keyring(secret: SecretName, value: String) -> miette::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, secret.name())?.set_password(&value)
+    })
+    .await
+    .map_err(|e| miette::miette!("Failed to write to the keyring: {}", e))?
+    .map_err(|e| {
+        miette::miette!(
+            help = "A keyring like GNOME Keyring or KWallet has to be running on Linux",
+            "Failed to store '{}' in the keyring: {}",
+            secret.name(),
+            e
+        )
+    })
+}
+
+/// Remove a secret from the OS keyring, returning whether it was stored
+pub(super) async fn remove_from_keyring(secret: SecretName) -> miette::Result<bool> {
+    let result = tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, secret.name())?.delete_credential()
+    })
+    .await
+    .map_err(|e| miette::miette!("Failed to write to the keyring: {}", e))?;
+    match result {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(miette::miette!(
+            "Failed to remove '{}' from the keyring: {}",
+            secret.name(),
+            e
+        )),
+    }
+}
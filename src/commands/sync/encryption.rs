@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    AeadCore, KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, OsRng, Payload, rand_core::RngCore},
+};
+
+use super::credentials::{self, SecretName};
+use crate::config::Config;
+
+/// Marks an encrypted remote database, followed by the format version
+const MAGIC: &[u8] = b"CLOGENC";
+const VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const HEADER_LENGTH: usize = MAGIC.len() + 1 + SALT_LENGTH + NONCE_LENGTH;
+
+/// Secret the key for the remote database is derived from,
+/// either a passphrase or the content of a key file
+pub(super) struct EncryptionSecret(Vec<u8>);
+
+impl EncryptionSecret {
+    /// Read the passphrase from the credential helper, the keyring or the
+    /// `CLOG_SYNC_PASSPHRASE` environment variable, falling back to the `sync.key_file`
+    /// setting. The remote database is not encrypted without one.
+    pub(super) async fn from_config(config: &Config) -> miette::Result<Option<Self>> {
+        if let Some((passphrase, _)) = credentials::resolve(config, SecretName::Passphrase).await? {
+            return Ok(Some(Self(passphrase.into_bytes())));
+        }
+        config
+            .sync
+            .key_file
+            .as_deref()
+            .map(Self::from_key_file)
+            .transpose()
+    }
+
+    fn from_key_file(path: &Path) -> miette::Result<Self> {
+        let content = std::fs::read(path)
+            .map_err(|e| miette::miette!("Failed to read key file {}: {}", path.display(), e))?;
+        if content.is_empty() {
+            return Err(miette::miette!("The key file {} is empty", path.display()));
+        }
+        Ok(Self(content))
+    }
+
+    fn cipher(&self, salt: &[u8]) -> miette::Result<XChaCha20Poly1305> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(&self.0, salt, &mut key)
+            .map_err(|e| miette::miette!("Failed to derive the encryption key: {}", e))?;
+        Ok(XChaCha20Poly1305::new(&key.into()))
+    }
+
+    /// Encrypt a database for the upload, with a new salt and nonce every time
+    pub(super) fn encrypt(&self, content: &[u8]) -> miette::Result<Vec<u8>> {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let mut header = Vec::with_capacity(HEADER_LENGTH);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+        // The header is authenticated as well, so it can't be tampered with
+        let ciphertext = self
+            .cipher(&salt)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: content,
+                    aad: &header,
+                },
+            )
+            .map_err(|e| miette::miette!("Failed to encrypt the database: {}", e))?;
+        header.extend_from_slice(&ciphertext);
+        Ok(header)
+    }
+}
+
+pub(super) fn is_encrypted(content: &[u8]) -> bool {
+    content.starts_with(MAGIC)
+}
+
+/// Decrypt a downloaded database. Unencrypted databases are only accepted without a key,
+/// otherwise anyone with write access to the storage could replace the encrypted database.
+pub(super) fn decrypt(
+    secret: Option<&EncryptionSecret>,
+    content: &[u8],
+) -> miette::Result<Vec<u8>> {
+    if !is_encrypted(content) {
+        if secret.is_some() {
+            return Err(miette::miette!(
+                help = "If encryption was enabled since the last upload, encrypt the remote database with `clog sync push --force`",
+                "The remote database is not encrypted, although an encryption key is configured"
+            ));
+        }
+        return Ok(content.to_vec());
+    }
+    let Some(secret) = secret else {
+        return Err(miette::miette!(
+            help = "Set the passphrase with `clog sync credential set passphrase`, in CLOG_SYNC_PASSPHRASE or a key file in `sync.key_file`",
+            "The remote database is encrypted"
+        ));
+    };
+    if content.len() < HEADER_LENGTH {
+        return Err(miette::miette!(
+            "The encrypted remote database is truncated"
+        ));
+    }
+    let (header, ciphertext) = content.split_at(HEADER_LENGTH);
+    let version = header[MAGIC.len()];
+    if version != VERSION {
+        return Err(miette::miette!(
+            help = "Update clog to sync with this database",
+            "The remote database is encrypted with an unknown format version {}",
+            version
+        ));
+    }
+    let salt = &header[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LENGTH];
+    let nonce = XNonce::from_slice(&header[MAGIC.len() + 1 + SALT_LENGTH..]);
+    secret
+        .cipher(salt)?
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| {
+            miette::miette!(
+                help = "Check the passphrase with `clog sync credential list` or the key file in `sync.key_file`",
+                "Failed to decrypt the remote database: the key is wrong or the database was modified"
+            )
+        })
+}
@@ -0,0 +1,155 @@
+use bytes::Bytes;
+use chrono::Utc;
+use futures::TryStreamExt;
+use object_store::{ObjectMeta, PutPayload, path::Path};
+
+use super::backend::SyncBackend;
+
+/// Directory next to the latest remote database which contains a copy of every upload
+const HISTORY_DIR: &str = "history";
+const VERSION_EXTENSION: &str = ".sqlite";
+/// Versions are named after the time of the upload, so they sort chronologically
+const VERSION_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+/// Marks a latest remote database which refers to a version instead of containing it
+const POINTER_MAGIC: &[u8] = b"CLOGREF";
+/// Pointers are far smaller, so larger remote files are never downloaded to check for one
+const POINTER_MAX_SIZE: u64 = 64;
+
+/// Previous upload of the remote database
+pub(super) struct RemoteVersion {
+    pub(super) id: String,
+    pub(super) meta: ObjectMeta,
+}
+
+/// Path of a version of the remote database
+pub(super) fn version_path(backend: &SyncBackend, id: &str) -> Path {
+    backend
+        .path(HISTORY_DIR)
+        .child(format!("{id}{VERSION_EXTENSION}"))
+}
+
+/// Store an upload as a new version before the latest remote database is pointed to it, so
+/// every version is exactly the database uploaded at its time. Returns the id of the version,
+/// `None` if `keep_versions` is 0.
+pub(super) async fn store(
+    backend: &SyncBackend,
+    payload: PutPayload,
+    keep_versions: usize,
+) -> miette::Result<Option<String>> {
+    if keep_versions == 0 {
+        return Ok(None);
+    }
+    let id = Utc::now().format(VERSION_FORMAT).to_string();
+    backend
+        .store()
+        .put(&version_path(backend, &id), payload)
+        .await
+        .map_err(|e| miette::miette!("Failed to store remote version {}: {}", id, e))?;
+    Ok(Some(id))
+}
+
+/// Content of the latest remote database referring to a version
+pub(super) fn pointer(id: &str) -> PutPayload {
+    [POINTER_MAGIC, id.as_bytes()].concat().into()
+}
+
+/// Version the latest remote database refers to, `None` if it contains the database itself
+pub(super) fn pointed_version(content: &[u8]) -> Option<&str> {
+    content
+        .strip_prefix(POINTER_MAGIC)
+        .and_then(|id| std::str::from_utf8(id).ok())
+}
+
+/// Download the version the latest remote database refers to
+pub(super) async fn get(backend: &SyncBackend, id: &str) -> miette::Result<Bytes> {
+    let get_result = backend
+        .store()
+        .get(&version_path(backend, id))
+        .await
+        .map_err(|e| match e {
+            object_store::Error::NotFound { .. } => miette::miette!(
+                help = "Use `clog sync push --force` to replace the latest remote database",
+                "The latest remote database refers to the missing version {}",
+                id
+            ),
+            e => miette::miette!("Failed to download remote version {}: {}", id, e),
+        })?;
+    get_result
+        .bytes()
+        .await
+        .map_err(|e| miette::miette!("Failed to read bytes of remote version {}: {}", id, e))
+}
+
+/// Metadata of the version the latest remote database refers to, or of the latest remote
+/// database itself if it contains the database
+pub(super) async fn resolve_meta(
+    backend: &SyncBackend,
+    meta: ObjectMeta,
+) -> miette::Result<ObjectMeta> {
+    if meta.size > POINTER_MAX_SIZE {
+        return Ok(meta);
+    }
+    let content = backend
+        .store()
+        .get(&meta.location)
+        .await
+        .map_err(|e| miette::miette!("Failed to download file from remote storage: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| miette::miette!("Failed to read bytes from downloaded file: {}", e))?;
+    let Some(id) = pointed_version(&content) else {
+        return Ok(meta);
+    };
+    backend
+        .store()
+        .head(&version_path(backend, id))
+        .await
+        .map_err(|e| miette::miette!("Failed to read metadata of remote version {}: {}", id, e))
+}
+
+/// Delete a version which never became the latest remote database
+pub(super) async fn discard(backend: &SyncBackend, id: &str) -> miette::Result<()> {
+    backend
+        .store()
+        .delete(&version_path(backend, id))
+        .await
+        .map_err(|e| miette::miette!("Failed to delete remote version {}: {}", id, e))
+}
+
+/// Delete the oldest versions beyond `keep_versions`
+pub(super) async fn prune(backend: &SyncBackend, keep_versions: usize) -> miette::Result<()> {
+    for version in list(backend).await?.into_iter().skip(keep_versions) {
+        backend
+            .store()
+            .delete(&version.meta.location)
+            .await
+            .map_err(|e| {
+                miette::miette!("Failed to delete remote version {}: {}", version.id, e)
+            })?;
+    }
+    Ok(())
+}
+
+/// All versions of the remote database, the newest first
+pub(super) async fn list(backend: &SyncBackend) -> miette::Result<Vec<RemoteVersion>> {
+    let history_path = backend.path(HISTORY_DIR);
+    let mut versions = backend
+        .store()
+        .list(Some(&history_path))
+        .map_ok(|meta| {
+            let id = meta
+                .location
+                .filename()
+                .and_then(|file_name| file_name.strip_suffix(VERSION_EXTENSION))
+                .map(str::to_string);
+            id.map(|id| RemoteVersion { id, meta })
+        })
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(|e| miette::miette!("Failed to list remote versions: {}", e))?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    versions.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(versions)
+}
@@ -0,0 +1,483 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ConnectionTrait, DatabaseConnection, prelude::*,
+};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::{
+    db,
+    entity::{projects, tasks, time_entries},
+};
+
+/// Table of a synced row, ordered so parents come before their children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(super) enum Table {
+    Project,
+    Task,
+    TimeEntry,
+}
+
+impl Table {
+    fn name(&self) -> &'static str {
+        match self {
+            Table::Project => "project",
+            Table::Task => "task",
+            Table::TimeEntry => "time_entry",
+        }
+    }
+}
+
+/// Values of a row which are merged, with references to other rows by their UUID
+#[derive(Debug, Clone, PartialEq)]
+enum RowData {
+    Project {
+        name: String,
+        description: Option<String>,
+        client: Option<String>,
+    },
+    Task {
+        project: String,
+        name: String,
+        description: Option<String>,
+        status: String,
+        tags: String,
+    },
+    TimeEntry {
+        task: String,
+        start_time: DateTimeWithTimeZone,
+        end_time: Option<DateTimeWithTimeZone>,
+        duration: i32,
+    },
+}
+
+impl RowData {
+    /// UUID of the row referenced by this row
+    fn parent(&self) -> Option<(Table, &str)> {
+        match self {
+            RowData::Project { .. } => None,
+            RowData::Task { project, .. } => Some((Table::Project, project)),
+            RowData::TimeEntry { task, .. } => Some((Table::Task, task)),
+        }
+    }
+
+    /// Human readable name of the row for the conflict report
+    fn label(&self) -> String {
+        match self {
+            RowData::Project { name, .. } | RowData::Task { name, .. } => name.clone(),
+            RowData::TimeEntry { start_time, .. } => start_time.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    data: RowData,
+    created_at: DateTimeWithTimeZone,
+    updated_at: DateTimeWithTimeZone,
+}
+
+type RowKey = (Table, String);
+
+/// All synced rows of a database, keyed by their table and UUID
+#[derive(Debug, Default)]
+pub(super) struct Snapshot {
+    rows: BTreeMap<RowKey, Row>,
+    /// Ids of the rows in the database the snapshot was taken from
+    ids: HashMap<RowKey, i32>,
+}
+
+impl Snapshot {
+    /// Read all synced rows of the database
+    pub(super) async fn load(db: &DatabaseConnection) -> miette::Result<Self> {
+        let mut snapshot = Snapshot::default();
+        let all_projects = projects::Entity::find()
+            .all(db)
+            .await
+            .map_err(|e| miette::miette!("Failed to fetch projects: {}", e))?;
+        let all_tasks = tasks::Entity::find()
+            .all(db)
+            .await
+            .map_err(|e| miette::miette!("Failed to fetch tasks: {}", e))?;
+        let all_time_entries = time_entries::Entity::find()
+            .all(db)
+            .await
+            .map_err(|e| miette::miette!("Failed to fetch time entries: {}", e))?;
+
+        let project_uuids: HashMap<i32, String> = all_projects
+            .iter()
+            .map(|project| (project.id, project.uuid.clone()))
+            .collect();
+        let task_uuids: HashMap<i32, String> = all_tasks
+            .iter()
+            .map(|task| (task.id, task.uuid.clone()))
+            .collect();
+        for project in all_projects {
+            snapshot.insert(
+                (Table::Project, project.uuid),
+                project.id,
+                Row {
+                    data: RowData::Project {
+                        name: project.name,
+                        description: project.description,
+                        client: project.client,
+                    },
+                    created_at: project.created_at,
+                    updated_at: project.updated_at,
+                },
+            );
+        }
+        for task in all_tasks {
+            let Some(project) = project_uuids.get(&task.project_id) else {
+                continue;
+            };
+            snapshot.insert(
+                (Table::Task, task.uuid),
+                task.id,
+                Row {
+                    data: RowData::Task {
+                        project: project.clone(),
+                        name: task.name,
+                        description: task.description,
+                        status: task.status,
+                        tags: task.tags,
+                    },
+                    created_at: task.created_at,
+                    updated_at: task.updated_at,
+                },
+            );
+        }
+        for entry in all_time_entries {
+            let Some(task) = task_uuids.get(&entry.task_id) else {
+                continue;
+            };
+            snapshot.insert(
+                (Table::TimeEntry, entry.uuid),
+                entry.id,
+                Row {
+                    data: RowData::TimeEntry {
+                        task: task.clone(),
+                        start_time: entry.start_time,
+                        end_time: entry.end_time,
+                        duration: entry.duration,
+                    },
+                    created_at: entry.created_at,
+                    updated_at: entry.updated_at,
+                },
+            );
+        }
+        Ok(snapshot)
+    }
+
+    /// Read all synced rows of a database file, validating and migrating it first
+    pub(super) async fn load_file(path: &Path) -> miette::Result<Self> {
+        let db = db::open_validated(path).await?;
+        let snapshot = Self::load(&db).await?;
+        db.close()
+            .await
+            .map_err(|e| miette::miette!("Failed to close database {}: {}", path.display(), e))?;
+        Ok(snapshot)
+    }
+
+    fn insert(&mut self, key: RowKey, id: i32, row: Row) {
+        self.ids.insert(key.clone(), id);
+        self.rows.insert(key, row);
+    }
+
+    /// Whether both snapshots contain the same rows with the same values
+    pub(super) fn same_rows(&self, other: &Snapshot) -> bool {
+        self.rows.len() == other.rows.len()
+            && self.rows.iter().all(|(key, row)| {
+                other
+                    .rows
+                    .get(key)
+                    .is_some_and(|other_row| other_row.data == row.data)
+            })
+    }
+}
+
+/// Number of rows of a table in the local and the remote database and how they differ
+#[derive(Tabled, Serialize, Clone)]
+pub(super) struct SyncDiffTable {
+    table: String,
+    local: u64,
+    remote: u64,
+    only_local: u64,
+    only_remote: u64,
+    changed: u64,
+}
+
+/// Compare the rows of the local and the remote database table by table
+pub(super) fn diff(local: &Snapshot, remote: &Snapshot) -> Vec<SyncDiffTable> {
+    [Table::Project, Table::Task, Table::TimeEntry]
+        .into_iter()
+        .map(|table| {
+            let mut diff = SyncDiffTable {
+                table: table.name().to_string(),
+                local: 0,
+                remote: 0,
+                only_local: 0,
+                only_remote: 0,
+                changed: 0,
+            };
+            for (key, row) in local.rows.iter().filter(|((t, _), _)| *t == table) {
+                diff.local += 1;
+                match remote.rows.get(key) {
+                    Some(remote_row) if remote_row.data != row.data => diff.changed += 1,
+                    Some(_) => {}
+                    None => diff.only_local += 1,
+                }
+            }
+            for key in remote.rows.keys().filter(|(t, _)| *t == table) {
+                diff.remote += 1;
+                if !local.rows.contains_key(key) {
+                    diff.only_remote += 1;
+                }
+            }
+            diff
+        })
+        .collect()
+}
+
+/// Row changed on both sides of a merge and how it was resolved
+#[derive(Tabled, Serialize, Clone)]
+pub(super) struct SyncConflictTable {
+    table: String,
+    uuid: String,
+    name: String,
+    resolution: String,
+}
+
+/// Result of a three-way merge of the local and the remote rows
+pub(super) struct Merge {
+    pub(super) merged: Snapshot,
+    pub(super) conflicts: Vec<SyncConflictTable>,
+}
+
+/// Merge the local and the remote rows, based on the rows of the last sync.
+/// Rows changed on one side only take the change, rows changed on both sides
+/// take the most recently updated row and are reported as conflicts.
+pub(super) fn merge(base: &Snapshot, local: &Snapshot, remote: &Snapshot) -> Merge {
+    let keys: BTreeSet<&RowKey> = local.rows.keys().chain(remote.rows.keys()).collect();
+    let mut merged = Snapshot::default();
+    let mut conflicts = vec![];
+    let mut conflict = |key: &RowKey, row: &Row, resolution: &str| {
+        conflicts.push(SyncConflictTable {
+            table: key.0.name().to_string(),
+            uuid: key.1.clone(),
+            name: row.data.label(),
+            resolution: resolution.to_string(),
+        });
+    };
+
+    for key in keys {
+        let base_row = base.rows.get(key);
+        let local_row = local.rows.get(key);
+        let remote_row = remote.rows.get(key);
+        let changed = |row: &Row| base_row.is_none_or(|base_row| base_row.data != row.data);
+        let row = match (local_row, remote_row) {
+            (Some(local_row), Some(remote_row)) if local_row.data == remote_row.data => {
+                Some(local_row)
+            }
+            (Some(local_row), Some(remote_row)) => {
+                match (changed(local_row), changed(remote_row)) {
+                    (false, _) => Some(remote_row),
+                    (true, false) => Some(local_row),
+                    (true, true) if local_row.updated_at >= remote_row.updated_at => {
+                        conflict(key, local_row, "kept local, changed on both sides");
+                        Some(local_row)
+                    }
+                    (true, true) => {
+                        conflict(key, remote_row, "kept remote, changed on both sides");
+                        Some(remote_row)
+                    }
+                }
+            }
+            // Rows missing on one side were either added on the other side or deleted
+            (Some(local_row), None) => match base_row {
+                None => Some(local_row),
+                Some(_) if changed(local_row) => {
+                    conflict(key, local_row, "kept local, deleted remotely");
+                    Some(local_row)
+                }
+                Some(_) => None,
+            },
+            (None, Some(remote_row)) => match base_row {
+                None => Some(remote_row),
+                Some(_) if changed(remote_row) => {
+                    conflict(key, remote_row, "kept remote, deleted locally");
+                    Some(remote_row)
+                }
+                Some(_) => None,
+            },
+            (None, None) => None,
+        };
+        if let Some(row) = row {
+            merged.rows.insert(key.clone(), row.clone());
+        }
+    }
+
+    // Restore deleted rows which are still referenced by a merged row, children are
+    // checked before their parents so restored tasks restore their projects as well
+    for table in [Table::TimeEntry, Table::Task] {
+        let missing_parents = merged
+            .rows
+            .iter()
+            .filter(|(key, _)| key.0 == table)
+            .filter_map(|(_, row)| row.data.parent())
+            .map(|(table, uuid)| (table, uuid.to_string()))
+            .filter(|parent| !merged.rows.contains_key(parent))
+            .collect::<BTreeSet<_>>();
+        for parent in missing_parents {
+            let Some(row) = [local, remote, base]
+                .iter()
+                .find_map(|snapshot| snapshot.rows.get(&parent))
+            else {
+                continue;
+            };
+            conflict(&parent, row, "restored, referenced by a changed row");
+            merged.rows.insert(parent, row.clone());
+        }
+    }
+
+    Merge { merged, conflicts }
+}
+
+/// Amount of rows changed in the local database by applying a merge
+#[derive(Debug, Default)]
+pub(super) struct MergeChanges {
+    pub(super) added: usize,
+    pub(super) updated: usize,
+    pub(super) deleted: usize,
+}
+
+/// Apply the merged rows to the local database, keeping the ids of existing rows
+pub(super) async fn apply<C>(
+    db: &C,
+    local: &Snapshot,
+    merged: &Snapshot,
+) -> miette::Result<MergeChanges>
+where
+    C: ConnectionTrait,
+{
+    let mut changes = MergeChanges::default();
+
+    // Delete children before their parents
+    for key in local
+        .rows
+        .keys()
+        .rev()
+        .filter(|key| !merged.rows.contains_key(*key))
+    {
+        let id = local.ids[key];
+        let result = match key.0 {
+            Table::Project => projects::Entity::delete_by_id(id).exec(db).await,
+            Table::Task => tasks::Entity::delete_by_id(id).exec(db).await,
+            Table::TimeEntry => time_entries::Entity::delete_by_id(id).exec(db).await,
+        };
+        result
+            .map_err(|e| miette::miette!("Failed to delete {} {}: {}", key.0.name(), key.1, e))?;
+        changes.deleted += 1;
+    }
+
+    // Insert parents before their children, so the ids of new parents are known
+    let mut ids = local.ids.clone();
+    for (key, row) in &merged.rows {
+        let local_row = local.rows.get(key);
+        if local_row.is_some_and(|local_row| local_row.data == row.data) {
+            continue;
+        }
+        let parent_id = match row.data.parent() {
+            Some((table, uuid)) => {
+                Some(*ids.get(&(table, uuid.to_string())).ok_or_else(|| {
+                    miette::miette!("Missing parent of {} {}", key.0.name(), key.1)
+                })?)
+            }
+            None => None,
+        };
+        let id = local.ids.get(key).copied();
+        let error = |e: DbErr| miette::miette!("Failed to save {} {}: {}", key.0.name(), key.1, e);
+        let saved_id = match &row.data {
+            RowData::Project {
+                name,
+                description,
+                client,
+            } => {
+                let mut project = projects::ActiveModel {
+                    name: Set(name.clone()),
+                    description: Set(description.clone()),
+                    client: Set(client.clone()),
+                    created_at: Set(row.created_at),
+                    updated_at: Set(row.updated_at),
+                    uuid: Set(key.1.clone()),
+                    ..Default::default()
+                };
+                if let Some(id) = id {
+                    project.id = Set(id);
+                    project.update(db).await.map_err(error)?.id
+                } else {
+                    project.insert(db).await.map_err(error)?.id
+                }
+            }
+            RowData::Task {
+                name,
+                description,
+                status,
+                tags,
+                ..
+            } => {
+                let mut task = tasks::ActiveModel {
+                    project_id: Set(parent_id.unwrap_or_default()),
+                    name: Set(name.clone()),
+                    description: Set(description.clone()),
+                    status: Set(status.clone()),
+                    tags: Set(tags.clone()),
+                    created_at: Set(row.created_at),
+                    updated_at: Set(row.updated_at),
+                    uuid: Set(key.1.clone()),
+                    ..Default::default()
+                };
+                if let Some(id) = id {
+                    task.id = Set(id);
+                    task.update(db).await.map_err(error)?.id
+                } else {
+                    task.insert(db).await.map_err(error)?.id
+                }
+            }
+            RowData::TimeEntry {
+                start_time,
+                end_time,
+                duration,
+                ..
+            } => {
+                let mut entry = time_entries::ActiveModel {
+                    task_id: Set(parent_id.unwrap_or_default()),
+                    start_time: Set(*start_time),
+                    end_time: Set(*end_time),
+                    duration: Set(*duration),
+                    created_at: Set(row.created_at),
+                    updated_at: Set(row.updated_at),
+                    uuid: Set(key.1.clone()),
+                    ..Default::default()
+                };
+                if let Some(id) = id {
+                    entry.id = Set(id);
+                    entry.update(db).await.map_err(error)?.id
+                } else {
+                    entry.insert(db).await.map_err(error)?.id
+                }
+            }
+        };
+        if id.is_some() {
+            changes.updated += 1;
+        } else {
+            changes.added += 1;
+        }
+        ids.insert(key.clone(), saved_id);
+    }
+    Ok(changes)
+}
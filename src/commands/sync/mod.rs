@@ -0,0 +1,935 @@
+use std::{io::IsTerminal, path::Path};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use object_store::{ObjectMeta, PutMode, PutPayload, UpdateVersion};
+use sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, TransactionTrait};
+use serde::Serialize;
+use tabled::Tabled;
+
+pub(super) use self::auto::{auto_sync_after, auto_sync_before};
+use self::{
+    backend::{PutOutcome, SyncBackend},
+    compression::is_compressed,
+    credentials::SecretName,
+    encryption::EncryptionSecret,
+    merge::{Merge, Snapshot, SyncDiffTable},
+    state::SyncState,
+};
+use super::CommandExecutorTrait;
+use crate::{
+    Context,
+    backup::{self, BackupReason},
+    commands::{
+        OutputOptions,
+        command_output::{CommandOutput, NoTable, format_time},
+    },
+    config::Config,
+    db::{self, sidecar_path},
+    entity::projects,
+    team,
+};
+
+mod auto;
+mod backend;
+mod compression;
+mod credentials;
+mod encryption;
+mod history;
+mod merge;
+mod state;
+
+const REMOTE_FILE_NAME: &str = "clog-db.sqlite";
+
+#[derive(Parser)]
+pub(super) struct SyncCommand {
+    /// Replace or inspect the whole database instead of merging the local and remote changes
+    #[clap(subcommand)]
+    command: Option<SyncSubcommand>,
+}
+
+#[derive(Subcommand)]
+pub(super) enum SyncSubcommand {
+    /// Push local changes to the remote storage
+    Push(SyncPushCommand),
+    /// Pull changes from the remote storage
+    Pull(SyncPullCommand),
+    /// Compare the remote database with the state of the last sync
+    Status(SyncStatusCommand),
+    /// List the previous versions of the remote database, the newest first
+    History(SyncHistoryCommand),
+    /// Manage the sync secrets in the OS keyring
+    #[clap(subcommand)]
+    Credential(SyncCredentialCommand),
+}
+
+#[derive(Parser)]
+pub(super) struct SyncPushCommand {
+    /// Overwrite the remote database even if it changed since the last sync
+    #[clap(short, long)]
+    force: bool,
+    /// Only show how the local and the remote rows differ, without uploading anything
+    #[clap(long)]
+    dry_run: bool,
+}
+
+#[derive(Parser)]
+pub(super) struct SyncPullCommand {
+    /// Overwrite the local database even if it has changes which were not pushed
+    #[clap(short, long)]
+    force: bool,
+    /// Only show how the local and the remote rows differ, without replacing anything
+    #[clap(long)]
+    dry_run: bool,
+    /// Pull a previous version of the remote database, as listed by `clog sync history`
+    #[clap(long)]
+    version: Option<String>,
+    /// Import the database of a teammate by their sync prefix for team reports,
+    /// without changing the local database
+    #[clap(long, conflicts_with_all = ["force", "dry_run"])]
+    from: Option<String>,
+}
+
+#[derive(Parser)]
+pub(super) struct SyncStatusCommand;
+
+#[derive(Parser)]
+pub(super) struct SyncHistoryCommand;
+
+#[derive(Subcommand)]
+pub(super) enum SyncCredentialCommand {
+    /// List the sync secrets and where they are found, without showing them
+    List(ListCredentialsCommand),
+    /// Store a secret in the OS keyring, read from the terminal or stdin
+    Set(SetCredentialCommand),
+    /// Remove a secret from the OS keyring
+    Remove(RemoveCredentialCommand),
+}
+
+#[derive(Parser)]
+pub(super) struct ListCredentialsCommand;
+
+#[derive(Parser)]
+pub(super) struct SetCredentialCommand {
+    /// Name of the secret
+    #[clap(value_enum)]
+    name: SecretName,
+}
+
+#[derive(Parser)]
+pub(super) struct RemoveCredentialCommand {
+    /// Name of the secret
+    #[clap(value_enum)]
+    name: SecretName,
+}
+
+impl CommandExecutorTrait for SyncCommand {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
+        // Secrets are managed without access to the remote storage
+        if let Some(SyncSubcommand::Credential(cmd)) = &self.command {
+            return credential(&ctx, output, cmd).await;
+        }
+        let backend = SyncBackend::from_config(&ctx.config).await?;
+        let secret = EncryptionSecret::from_config(&ctx.config).await?;
+        let secret = secret.as_ref();
+
+        match &self.command {
+            None => sync(ctx, output, &backend, secret).await,
+            Some(SyncSubcommand::Push(cmd)) if cmd.dry_run => {
+                dry_run(ctx, output, &backend, secret, Direction::Push, None).await
+            }
+            Some(SyncSubcommand::Push(cmd)) => push(ctx, output, &backend, secret, cmd.force).await,
+            Some(SyncSubcommand::Pull(SyncPullCommand {
+                from: Some(from),
+                version,
+                ..
+            })) => {
+                import(
+                    &ctx.config,
+                    output,
+                    &backend,
+                    secret,
+                    from,
+                    version.as_deref(),
+                )
+                .await
+            }
+            Some(SyncSubcommand::Pull(cmd)) if cmd.dry_run => {
+                let version = cmd.version.as_deref();
+                dry_run(ctx, output, &backend, secret, Direction::Pull, version).await
+            }
+            Some(SyncSubcommand::Pull(cmd)) => {
+                let version = cmd.version.as_deref();
+                pull(ctx, output, &backend, secret, version, cmd.force).await
+            }
+            Some(SyncSubcommand::Status(_)) => status(&ctx.config, output, &backend).await,
+            Some(SyncSubcommand::History(_)) => history(output, &backend).await,
+            Some(SyncSubcommand::Credential(_)) => unreachable!("handled above"),
+        }
+    }
+}
+
+/// Merge the local and the remote changes row by row and upload the merged database
+async fn sync(
+    ctx: Context,
+    output: &OutputOptions,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+) -> miette::Result<()> {
+    let db_path = db::db_path(&ctx.config)
+        .map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let state = SyncState::load(&db_path, backend.url())?;
+
+    let location = remote_location(backend, None);
+    let (remote, remote_version, remote_packed) =
+        match download(backend, secret, &location, &db_path).await? {
+            Some(remote) => (remote.snapshot, Some(remote.version), remote.packed),
+            None => (Snapshot::default(), None, false),
+        };
+    // Without a previous sync, every row is treated as added on its side
+    let base_path = SyncState::base_path(&db_path);
+    let base = match state {
+        Some(_) if base_path.exists() => Snapshot::load_file(&base_path).await?,
+        _ => Snapshot::default(),
+    };
+    let local = Snapshot::load(&ctx.db).await?;
+    let Merge { merged, conflicts } = merge::merge(&base, &local, &remote);
+    if !local.same_rows(&merged) {
+        backup::create(&ctx.db, &db_path, BackupReason::Merge, &ctx.config.backup).await?;
+    }
+
+    let txn = ctx
+        .db
+        .begin()
+        .await
+        .map_err(|e| miette::miette!("Failed to start transaction: {}", e))?;
+    let changes = merge::apply(&txn, &local, &merged).await?;
+    txn.commit()
+        .await
+        .map_err(|e| miette::miette!("Failed to commit merged changes: {}", e))?;
+
+    let snapshot_bytes = Bytes::from(db::snapshot(&ctx.db, &db_path).await?);
+    let (remote_e_tag, remote_version) = match remote_version {
+        // The remote database already contains all merged rows and is compressed,
+        // and encrypted unless encryption was enabled or disabled since the last upload
+        Some(remote_version) if remote.same_rows(&merged) && remote_packed => {
+            (remote_version.e_tag, remote_version.version)
+        }
+        remote_version => {
+            let put_mode = match remote_version {
+                Some(remote_version) => PutMode::Update(remote_version),
+                None => PutMode::Create,
+            };
+            let put_payload = upload_payload(secret, &snapshot_bytes)?;
+            match put_latest(backend, &ctx.config, put_payload, put_mode).await? {
+                PutOutcome::Stored(put_result) => (put_result.e_tag, put_result.version),
+                PutOutcome::Conflict => {
+                    return Err(miette::miette!(
+                        help = "Run `clog sync` again to merge the new remote changes",
+                        "The remote database changed while syncing"
+                    ));
+                }
+            }
+        }
+    };
+    let local_bytes = read_database_file(&db_path).await?;
+    SyncState::synced(backend.url(), remote_e_tag, remote_version, &local_bytes)
+        .save(&db_path, &snapshot_bytes)?;
+    prune_history(backend, &ctx.config).await;
+
+    let mut sync_output = CommandOutput::builder()
+        .with_options(output)
+        .with_prefix_message(format!(
+            "Local and remote changes merged successfully: {} added, {} updated and {} deleted locally.",
+            changes.added, changes.updated, changes.deleted
+        ));
+    if !conflicts.is_empty() {
+        sync_output = sync_output
+            .with_prefix_message(format!(
+                "{} conflicting changes were resolved:",
+                conflicts.len()
+            ))
+            .with_table_rows(conflicts);
+    }
+    sync_output.build().print()?;
+    Ok(())
+}
+
+async fn push(
+    ctx: Context,
+    output: &OutputOptions,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    force: bool,
+) -> miette::Result<()> {
+    upload(&ctx.db, &ctx.config, backend, secret, force).await?;
+
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message("Local changes pushed to remote storage successfully.".to_string())
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Replace the remote database with the local one, unless the remote database changed
+/// since the last sync
+async fn upload(
+    db: &DatabaseConnection,
+    config: &Config,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    force: bool,
+) -> miette::Result<()> {
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+
+    let snapshot_bytes = Bytes::from(db::snapshot(db, &db_path).await?);
+    // Only replace the remote database if it is still the one of the last sync
+    let state = SyncState::load(&db_path, backend.url())?;
+    let put_mode = match (&state, force) {
+        (_, true) => PutMode::Overwrite,
+        (Some(state), false) => PutMode::Update(state.update_version()),
+        (None, false) => PutMode::Create,
+    };
+
+    let put_payload = upload_payload(secret, &snapshot_bytes)?;
+    let put_result = match put_latest(backend, config, put_payload, put_mode).await? {
+        PutOutcome::Stored(put_result) => put_result,
+        PutOutcome::Conflict if state.is_none() => {
+            return Err(miette::miette!(
+                help = "Pull the remote database first, or use `clog sync push --force` to overwrite it",
+                "The remote storage already contains a database which was never synced with this one"
+            ));
+        }
+        PutOutcome::Conflict => {
+            return Err(miette::miette!(
+                help = "Pull the remote changes first, or use `clog sync push --force` to overwrite them",
+                "The remote database has changed since the last sync"
+            ));
+        }
+    };
+
+    let local_bytes = read_database_file(&db_path).await?;
+    SyncState::synced(
+        backend.url(),
+        put_result.e_tag,
+        put_result.version,
+        &local_bytes,
+    )
+    .save(&db_path, &snapshot_bytes)?;
+    prune_history(backend, config).await;
+    Ok(())
+}
+
+/// Store the payload as a new version of the remote history, then point the latest remote
+/// database to it if the condition of the put mode holds, so the payload is uploaded once.
+/// Without a history the payload replaces the latest remote database itself. A version which
+/// didn't become the latest one is deleted again.
+async fn put_latest(
+    backend: &SyncBackend,
+    config: &Config,
+    payload: PutPayload,
+    put_mode: PutMode,
+) -> miette::Result<PutOutcome> {
+    let version = history::store(backend, payload.clone(), config.sync.keep_versions).await?;
+    let latest = match &version {
+        Some(version) => history::pointer(version),
+        None => payload,
+    };
+    let outcome = backend
+        .put_conditional(&backend.path(REMOTE_FILE_NAME), latest, put_mode)
+        .await;
+    if !matches!(outcome, Ok(PutOutcome::Stored(_)))
+        && let Some(version) = version
+        && let Err(e) = history::discard(backend, &version).await
+    {
+        eprintln!("Warning: {e}");
+    }
+    outcome
+}
+
+/// Delete the oldest remote versions. The upload already succeeded at this point,
+/// so a failure is only a warning.
+async fn prune_history(backend: &SyncBackend, config: &Config) {
+    if let Err(e) = history::prune(backend, config.sync.keep_versions).await {
+        eprintln!("Warning: {e}");
+    }
+}
+
+async fn pull(
+    ctx: Context,
+    output: &OutputOptions,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    version: Option<&str>,
+    force: bool,
+) -> miette::Result<()> {
+    let (remote_meta, file_bytes) = fetch(backend, secret, version).await?;
+    let message = replace_local(
+        ctx.db,
+        &ctx.config,
+        backend,
+        version,
+        remote_meta,
+        &file_bytes,
+        force,
+    )
+    .await?;
+
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(message)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Download the remote database, or one of its previous versions, and unpack it
+async fn fetch(
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    version: Option<&str>,
+) -> miette::Result<(ObjectMeta, Vec<u8>)> {
+    let remote_file = get_unpacked(backend, secret, &remote_location(backend, version))
+        .await?
+        .ok_or_else(|| not_found_error(version))?;
+    Ok((remote_file.meta, remote_file.bytes))
+}
+
+/// Replace the local database with a downloaded one, unless the local database has
+/// changes which were never pushed
+async fn replace_local(
+    db: DatabaseConnection,
+    config: &Config,
+    backend: &SyncBackend,
+    version: Option<&str>,
+    remote_meta: ObjectMeta,
+    file_bytes: &[u8],
+    force: bool,
+) -> miette::Result<String> {
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+
+    // Refuse to replace local changes which were never pushed
+    if !force {
+        let local_bytes = read_database_file(&db_path).await?;
+        match SyncState::load(&db_path, backend.url())? {
+            Some(state) if state.local_changed(&local_bytes) => {
+                // A previous version replaces the local changes regardless of the remote changes
+                if version.is_some() || state.matches_remote(&remote_meta) {
+                    return Err(miette::miette!(
+                        help = "Push the local changes first, or use `clog sync pull --force` to discard them",
+                        "The local database has changes which were not pushed yet"
+                    ));
+                }
+                return Err(miette::miette!(
+                    help = "Use `clog sync pull --force` to discard the local changes, or `clog sync push --force` to discard the remote changes",
+                    "The local and the remote database have both changed since the last sync"
+                ));
+            }
+            Some(_) => {}
+            None => {
+                let local_projects = projects::Entity::find()
+                    .count(&db)
+                    .await
+                    .map_err(|e| miette::miette!("Failed to count projects: {}", e))?;
+                if local_projects > 0 {
+                    return Err(miette::miette!(
+                        help = "Use `clog sync pull --force` to replace the local database",
+                        "The local database contains data which was never synced with the remote storage"
+                    ));
+                }
+            }
+        }
+    }
+
+    // Validate the downloaded database next to the local one, so it can be renamed over it
+    let download_path = sidecar_path(&db_path, ".sync-download.sqlite");
+    write_validated(&download_path, file_bytes).await?;
+    backup::create(&db, &db_path, BackupReason::Pull, &config.backup).await?;
+    db::replace(db, &db_path, &download_path).await?;
+
+    let message = match version {
+        // The previous version differs from the latest remote database, so it is treated
+        // as never synced and has to be pushed with `--force` to restore it remotely
+        Some(version) => {
+            SyncState::remove(&db_path)?;
+            format!(
+                "Version {version} pulled from remote storage successfully. Use `clog sync push --force` to make it the latest remote version."
+            )
+        }
+        None => {
+            let local_bytes = read_database_file(&db_path).await?;
+            SyncState::synced(
+                backend.url(),
+                remote_meta.e_tag,
+                remote_meta.version,
+                &local_bytes,
+            )
+            .save(&db_path, &local_bytes)?;
+            "Changes pulled from remote storage successfully.".to_string()
+        }
+    };
+    Ok(message)
+}
+
+/// Download the database of a teammate next to the local database, where it is read
+/// by team reports
+async fn import(
+    config: &Config,
+    output: &OutputOptions,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    from: &str,
+    version: Option<&str>,
+) -> miette::Result<()> {
+    import_member(config, backend, secret, from, version).await?;
+
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(format!(
+            "Database of '{from}' imported for team reports successfully."
+        ))
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Import the latest databases of the teammates with the configured sync settings
+pub(super) async fn import_team_members(config: &Config, members: &[String]) -> miette::Result<()> {
+    let backend = SyncBackend::from_config(config).await?;
+    let secret = EncryptionSecret::from_config(config).await?;
+    for member in members {
+        import_member(config, &backend, secret.as_ref(), member, None).await?;
+    }
+    Ok(())
+}
+
+async fn import_member(
+    config: &Config,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    from: &str,
+    version: Option<&str>,
+) -> miette::Result<()> {
+    let member_backend = backend.with_prefix(from)?;
+    let location = remote_location(&member_backend, version);
+    let remote_file = match get_unpacked(&member_backend, secret, &location).await? {
+        Some(remote_file) => remote_file,
+        None if version.is_none() => {
+            return Err(miette::miette!(
+                help =
+                    "Check the sync prefix of the teammate, it has to be relative to the sync URL",
+                "The remote storage contains no database at {}",
+                member_backend.url()
+            ));
+        }
+        None => return Err(not_found_error(version)),
+    };
+
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let import_path = team::import_path(&db_path, from);
+    let import_dir = team::import_dir(&db_path);
+    tokio::fs::create_dir_all(&import_dir).await.map_err(|e| {
+        miette::miette!(
+            "Failed to create team directory {}: {}",
+            import_dir.display(),
+            e
+        )
+    })?;
+    let download_path = sidecar_path(&import_path, ".download");
+    write_validated(&download_path, &remote_file.bytes).await?;
+    tokio::fs::rename(&download_path, &import_path)
+        .await
+        .map_err(|e| {
+            miette::miette!(
+                "Failed to replace imported database at {}: {}",
+                import_path.display(),
+                e
+            )
+        })?;
+    Ok(())
+}
+
+/// Write a downloaded database to a file and check that it is intact and can be migrated,
+/// the file is removed if it can't be used
+async fn write_validated(path: &Path, content: &[u8]) -> miette::Result<()> {
+    tokio::fs::write(path, content).await.map_err(|e| {
+        miette::miette!(
+            "Failed to write downloaded database to {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let downloaded_db = match db::open_validated(path).await {
+        Ok(downloaded_db) => downloaded_db,
+        Err(e) => {
+            db::remove_if_exists(path).await?;
+            return Err(e);
+        }
+    };
+    downloaded_db
+        .close()
+        .await
+        .map_err(|e| miette::miette!("Failed to close downloaded database: {}", e))
+}
+
+/// Location of the latest remote database or of a previous version of it
+fn remote_location(backend: &SyncBackend, version: Option<&str>) -> object_store::path::Path {
+    match version {
+        Some(version) => history::version_path(backend, version),
+        None => backend.path(REMOTE_FILE_NAME),
+    }
+}
+
+fn not_found_error(version: Option<&str>) -> miette::Report {
+    match version {
+        Some(version) => miette::miette!(
+            help = "Use `clog sync history` to list the versions of the remote database",
+            "The remote storage contains no version {}",
+            version
+        ),
+        None => miette::miette!("The remote storage contains no database to pull"),
+    }
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct SyncHistoryTable {
+    version: String,
+    size: u64,
+    last_modified: String,
+}
+
+async fn history(output: &OutputOptions, backend: &SyncBackend) -> miette::Result<()> {
+    let history_table = history::list(backend)
+        .await?
+        .into_iter()
+        .map(|version| SyncHistoryTable {
+            version: version.id,
+            size: version.meta.size,
+            last_modified: format_time(version.meta.last_modified),
+        })
+        .collect::<Vec<_>>();
+
+    CommandOutput::<Vec<SyncHistoryTable>, SyncHistoryTable>::builder()
+        .with_options(output)
+        .with_prefix_message(format!(
+            "All versions of the remote database at {}",
+            backend.url()
+        ))
+        .with_table_rows(history_table)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+#[derive(Tabled, Serialize, Clone)]
+struct SyncCredentialTable {
+    name: String,
+    source: String,
+}
+
+/// List, store or remove the sync secrets. The secrets themselves are never printed.
+async fn credential(
+    ctx: &Context,
+    output: &OutputOptions,
+    cmd: &SyncCredentialCommand,
+) -> miette::Result<()> {
+    let message = match cmd {
+        SyncCredentialCommand::List(_) => {
+            let mut credential_table = vec![];
+            for secret in SecretName::value_variants() {
+                let source = credentials::resolve(&ctx.config, *secret)
+                    .await?
+                    .map(|(_, source)| source.to_string());
+                credential_table.push(SyncCredentialTable {
+                    name: secret.name().to_string(),
+                    source: source.unwrap_or_else(|| "-".to_string()),
+                });
+            }
+            CommandOutput::<Vec<SyncCredentialTable>, SyncCredentialTable>::builder()
+                .with_options(output)
+                .with_prefix_message("Sources of the sync secrets".to_string())
+                .with_table_rows(credential_table)
+                .build()
+                .print()?;
+            return Ok(());
+        }
+        SyncCredentialCommand::Set(cmd) => {
+            let value = read_secret(cmd.name)?;
+            credentials::store_in_keyring(cmd.name, value).await?;
+            format!("Secret '{}' stored in the keyring.", cmd.name.name())
+        }
+        SyncCredentialCommand::Remove(cmd) => {
+            if credentials::remove_from_keyring(cmd.name).await? {
+                format!("Secret '{}' removed from the keyring.", cmd.name.name())
+            } else {
+                format!("Secret '{}' is not stored in the keyring.", cmd.name.name())
+            }
+        }
+    };
+    CommandOutput::<Vec<NoTable>, NoTable>::builder()
+        .with_options(output)
+        .with_prefix_message(message)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Read a secret without echoing it in a terminal, or the first line of stdin otherwise
+fn read_secret(secret: SecretName) -> miette::Result<String> {
+    let value = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password(format!("Secret '{}': ", secret.name()))
+            .map_err(|e| miette::miette!("Failed to read the secret: {}", e))?
+    } else {
+        let mut value = String::new();
+        std::io::stdin()
+            .read_line(&mut value)
+            .map_err(|e| miette::miette!("Failed to read the secret from stdin: {}", e))?;
+        value.trim_end_matches(['\r', '\n']).to_string()
+    };
+    if value.is_empty() {
+        return Err(miette::miette!("The secret '{}' is empty", secret.name()));
+    }
+    Ok(value)
+}
+
+/// Direction in which a whole database is replaced
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Push,
+    Pull,
+}
+
+/// Show how the rows of the local and the remote database differ, without changing either
+async fn dry_run(
+    ctx: Context,
+    output: &OutputOptions,
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    direction: Direction,
+    version: Option<&str>,
+) -> miette::Result<()> {
+    let db_path = db::db_path(&ctx.config)
+        .map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let location = remote_location(backend, version);
+    let remote = match download(backend, secret, &location, &db_path).await? {
+        Some(remote) => remote.snapshot,
+        None if matches!(direction, Direction::Pull) => {
+            return Err(not_found_error(version));
+        }
+        None => Snapshot::default(),
+    };
+    let local = Snapshot::load(&ctx.db).await?;
+
+    let message = match direction {
+        Direction::Push => "Dry run: pushing would replace the remote rows with the local ones",
+        Direction::Pull => "Dry run: pulling would replace the local rows with the remote ones",
+    };
+    CommandOutput::<Vec<SyncDiffTable>, SyncDiffTable>::builder()
+        .with_options(output)
+        .with_prefix_message(message.to_string())
+        .with_table_rows(merge::diff(&local, &remote))
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Local or remote database compared by `clog sync status`
+#[derive(Tabled, Serialize, Clone)]
+struct SyncStatusTable {
+    database: String,
+    size: u64,
+    last_modified: String,
+    e_tag: String,
+    /// Whether the database changed since the last sync
+    changed: bool,
+}
+
+/// Compare the local database and the metadata of the remote database with the state
+/// of the last sync, without downloading the remote database
+async fn status(
+    config: &Config,
+    output: &OutputOptions,
+    backend: &SyncBackend,
+) -> miette::Result<()> {
+    let db_path =
+        db::db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let state = SyncState::load(&db_path, backend.url())?;
+
+    let local_bytes = read_database_file(&db_path).await?;
+    let local_modified = tokio::fs::metadata(&db_path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| {
+            miette::miette!(
+                "Failed to read metadata of database file at {}: {}",
+                db_path.display(),
+                e
+            )
+        })?;
+    let local_changed = state
+        .as_ref()
+        .is_none_or(|state| state.local_changed(&local_bytes));
+    let mut status_table = vec![SyncStatusTable {
+        database: "local".to_string(),
+        size: local_bytes.len() as u64,
+        last_modified: format_time(DateTime::<Utc>::from(local_modified)),
+        // The local database is compared with the remote one by the ETag of the last sync
+        e_tag: state
+            .as_ref()
+            .and_then(|state| state.remote_e_tag.clone())
+            .unwrap_or_default(),
+        changed: local_changed,
+    }];
+
+    let remote_meta = match backend.store().head(&backend.path(REMOTE_FILE_NAME)).await {
+        Ok(remote_meta) => Some(remote_meta),
+        Err(object_store::Error::NotFound { .. }) => None,
+        Err(e) => {
+            return Err(miette::miette!(
+                "Failed to read metadata from remote storage: {}",
+                e
+            ));
+        }
+    };
+    let remote_changed = remote_meta.as_ref().is_some_and(|remote_meta| {
+        state
+            .as_ref()
+            .is_none_or(|state| !state.matches_remote(remote_meta))
+    });
+    if let Some(remote_meta) = remote_meta {
+        // The ETag is the one of the latest remote database, the size the one of its version
+        let database_meta = history::resolve_meta(backend, remote_meta.clone()).await?;
+        status_table.push(SyncStatusTable {
+            database: "remote".to_string(),
+            size: database_meta.size,
+            last_modified: format_time(database_meta.last_modified),
+            e_tag: remote_meta.e_tag.unwrap_or_default(),
+            changed: remote_changed,
+        });
+    }
+
+    let summary = match (&state, local_changed, remote_changed) {
+        (None, _, _) => "The database was never synced with this remote storage.",
+        (Some(_), false, false) => "The local and the remote database are in sync.",
+        (Some(_), true, false) => "The local database has changes which were not pushed yet.",
+        (Some(_), false, true) => "The remote database is newer than the local one.",
+        (Some(_), true, true) => {
+            "The local and the remote database have both changed since the last sync."
+        }
+    };
+    CommandOutput::<Vec<SyncStatusTable>, SyncStatusTable>::builder()
+        .with_options(output)
+        .with_prefix_message(format!("Sync status of {}:", backend.url()))
+        .with_prefix_message(summary.to_string())
+        .with_table_rows(status_table)
+        .build()
+        .print()?;
+    Ok(())
+}
+
+/// Remote database downloaded for a merge or a comparison
+struct RemoteDatabase {
+    snapshot: Snapshot,
+    version: UpdateVersion,
+    /// Whether the remote database was uploaded with the current compression and encryption,
+    /// rather than by an older version of clog or with other settings
+    packed: bool,
+}
+
+/// Download and read the remote database, `None` if the remote storage contains none yet
+async fn download(
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    location: &object_store::path::Path,
+    db_path: &Path,
+) -> miette::Result<Option<RemoteDatabase>> {
+    let Some(remote_file) = get_unpacked(backend, secret, location).await? else {
+        return Ok(None);
+    };
+    let remote_path = sidecar_path(db_path, ".sync-remote.sqlite");
+    write_validated(&remote_path, &remote_file.bytes).await?;
+    let snapshot = Snapshot::load_file(&remote_path).await;
+    db::remove_if_exists(&remote_path).await?;
+    Ok(Some(RemoteDatabase {
+        snapshot: snapshot?,
+        version: UpdateVersion {
+            e_tag: remote_file.meta.e_tag,
+            version: remote_file.meta.version,
+        },
+        packed: remote_file.packed,
+    }))
+}
+
+/// Remote file downloaded and unpacked
+struct RemoteFile {
+    meta: ObjectMeta,
+    /// Decrypted and decompressed content
+    bytes: Vec<u8>,
+    /// Whether the file is compressed, and encrypted exactly if a secret is configured
+    packed: bool,
+}
+
+/// Download a remote file, then decrypt and decompress it, `None` if the remote storage
+/// contains no file at the location
+async fn get_unpacked(
+    backend: &SyncBackend,
+    secret: Option<&EncryptionSecret>,
+    location: &object_store::path::Path,
+) -> miette::Result<Option<RemoteFile>> {
+    let get_result = match backend.store().get(location).await {
+        Ok(get_result) => get_result,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(e) => {
+            return Err(miette::miette!(
+                "Failed to download file from remote storage: {}",
+                e
+            ));
+        }
+    };
+    // The metadata of the latest remote database is kept for the conditional uploads,
+    // even if it refers to a version
+    let meta = get_result.meta.clone();
+    let mut file_bytes = get_result
+        .bytes()
+        .await
+        .map_err(|e| miette::miette!("Failed to read bytes from downloaded file: {}", e))?;
+    if let Some(version) = history::pointed_version(&file_bytes).map(str::to_string) {
+        file_bytes = history::get(backend, &version).await?;
+    }
+    let encrypted = encryption::is_encrypted(&file_bytes);
+    let file_bytes = encryption::decrypt(secret, &file_bytes)?;
+    let packed = encrypted == secret.is_some() && is_compressed(&file_bytes);
+    Ok(Some(RemoteFile {
+        meta,
+        bytes: compression::decompress(&file_bytes)?,
+        packed,
+    }))
+}
+
+/// Payload of a database snapshot uploaded to the remote storage, compressed and
+/// then encrypted if an encryption secret is configured
+fn upload_payload(
+    secret: Option<&EncryptionSecret>,
+    snapshot_bytes: &Bytes,
+) -> miette::Result<object_store::PutPayload> {
+    let compressed = compression::compress(snapshot_bytes)?;
+    match secret {
+        Some(secret) => Ok(secret.encrypt(&compressed)?.into()),
+        None => Ok(compressed.into()),
+    }
+}
+
+async fn read_database_file(db_path: &Path) -> miette::Result<Vec<u8>> {
+    tokio::fs::read(db_path).await.map_err(|e| {
+        miette::miette!(
+            "Failed to read database file at {}: {}",
+            db_path.display(),
+            e
+        )
+    })
+}
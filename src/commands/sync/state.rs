@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+use object_store::{ObjectMeta, UpdateVersion};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::db::sidecar_path;
+
+const STATE_SUFFIX: &str = ".sync-state.json";
+const BASE_SUFFIX: &str = ".sync-base.sqlite";
+
+/// State of the last successful sync, stored in a sidecar file next to the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SyncState {
+    /// URL of the backend the database was synced with
+    pub(super) remote_url: String,
+    /// ETag of the remote database after the last sync
+    pub(super) remote_e_tag: Option<String>,
+    /// Version of the remote database after the last sync, if the backend supports versioning
+    pub(super) remote_version: Option<String>,
+    /// SHA-256 hash of the local database after the last sync
+    pub(super) local_hash: String,
+}
+
+impl SyncState {
+    /// Load the state of the last sync of the database with the backend,
+    /// `None` if the database was never synced with it
+    pub(super) fn load(db_path: &Path, remote_url: &str) -> miette::Result<Option<Self>> {
+        let path = sidecar_path(db_path, STATE_SUFFIX);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            miette::miette!("Failed to read sync state file {}: {}", path.display(), e)
+        })?;
+        let state: SyncState = serde_json::from_str(&content)
+            .map_err(|e| miette::miette!("Invalid sync state file {}: {}", path.display(), e))?;
+        Ok(Some(state).filter(|state| state.remote_url == remote_url))
+    }
+
+    /// State after the database was synced with the remote database of the given version
+    pub(super) fn synced(
+        remote_url: &str,
+        remote_e_tag: Option<String>,
+        remote_version: Option<String>,
+        content: &[u8],
+    ) -> Self {
+        Self {
+            remote_url: remote_url.to_string(),
+            remote_e_tag,
+            remote_version,
+            local_hash: content_hash(content),
+        }
+    }
+
+    /// Save the state together with a copy of the synced database, which is the base
+    /// of the next merge
+    pub(super) fn save(&self, db_path: &Path, content: &[u8]) -> miette::Result<()> {
+        let base_path = sidecar_path(db_path, BASE_SUFFIX);
+        std::fs::write(&base_path, content).map_err(|e| {
+            miette::miette!(
+                "Failed to write sync base file {}: {}",
+                base_path.display(),
+                e
+            )
+        })?;
+        let path = sidecar_path(db_path, STATE_SUFFIX);
+        let state = serde_json::to_string_pretty(self)
+            .map_err(|e| miette::miette!("Failed to serialize sync state: {}", e))?;
+        std::fs::write(&path, state).map_err(|e| {
+            miette::miette!("Failed to write sync state file {}: {}", path.display(), e)
+        })
+    }
+
+    /// Forget the last sync, e.g. after the database was replaced by an older version
+    pub(super) fn remove(db_path: &Path) -> miette::Result<()> {
+        for suffix in [STATE_SUFFIX, BASE_SUFFIX] {
+            let path = sidecar_path(db_path, suffix);
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(miette::miette!(
+                        "Failed to remove sync file {}: {}",
+                        path.display(),
+                        e
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the copy of the database as of the last sync
+    pub(super) fn base_path(db_path: &Path) -> PathBuf {
+        sidecar_path(db_path, BASE_SUFFIX)
+    }
+
+    /// Version the remote database is expected to have when it is updated
+    pub(super) fn update_version(&self) -> UpdateVersion {
+        UpdateVersion {
+            e_tag: self.remote_e_tag.clone(),
+            version: self.remote_version.clone(),
+        }
+    }
+
+    /// Whether the remote database is still the one of the last sync
+    pub(super) fn matches_remote(&self, meta: &ObjectMeta) -> bool {
+        self.remote_e_tag == meta.e_tag && self.remote_version == meta.version
+    }
+
+    /// Whether the local database was modified since the last sync
+    pub(super) fn local_changed(&self, content: &[u8]) -> bool {
+        self.local_hash != content_hash(content)
+    }
+}
+
+fn content_hash(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
@@ -1,7 +1,9 @@
+use std::collections::BTreeSet;
+
 use clap::{Parser, Subcommand};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, DatabaseTransaction, IntoActiveModel, TransactionTrait,
-    TryIntoModel, prelude::*,
+    ActiveModelTrait, ActiveValue::Set, DatabaseTransaction, FromQueryResult, IntoActiveModel,
+    QueryOrder, QuerySelect, TransactionTrait, TryIntoModel, prelude::*,
 };
 use serde::Serialize;
 use tabled::Tabled;
@@ -10,7 +12,7 @@ use super::CommandExecutorTrait;
 use crate::{
     Context,
     commands::{
-        OutputFormat,
+        OutputOptions,
         command_output::{CommandOutput, NoTable},
     },
     entity::{projects, tasks, time_entries},
@@ -57,6 +59,9 @@ pub(super) struct CreateTaskCommand {
     /// Immediately start the task after creation
     #[clap(short, long)]
     start: bool,
+    /// Comma separated tags of the task, reports can group the tasks by them
+    #[clap(short, long, value_delimiter = ',')]
+    tags: Vec<String>,
 }
 
 #[derive(Parser)]
@@ -104,10 +109,13 @@ pub(super) struct EditTaskCommand {
     /// New description for the task
     #[clap(short = 'd', long)]
     new_description: Option<String>,
+    /// New comma separated tags for the task, replacing the current ones
+    #[clap(short = 't', long, value_delimiter = ',')]
+    new_tags: Option<Vec<String>>,
 }
 
 impl CommandExecutorTrait for TaskCommand {
-    async fn execute(&self, ctx: Context, output_format: OutputFormat) -> miette::Result<()> {
+    async fn execute(&self, ctx: Context, output: &OutputOptions) -> miette::Result<()> {
         match self {
             TaskCommand::Create(cmd) => {
                 create(
@@ -116,23 +124,25 @@ impl CommandExecutorTrait for TaskCommand {
                     cmd.description.as_deref(),
                     &cmd.project_name,
                     cmd.start,
-                    output_format,
+                    &cmd.tags,
+                    output,
                 )
                 .await
             }
-            TaskCommand::Remove(cmd) => remove(&ctx, &cmd.name, output_format).await,
-            TaskCommand::List(cmd) => list(&ctx, &cmd.project_name, output_format).await,
-            TaskCommand::Start(cmd) => start(&ctx, &cmd.name, output_format).await,
+            TaskCommand::Remove(cmd) => remove(&ctx, &cmd.name, output).await,
+            TaskCommand::List(cmd) => list(&ctx, &cmd.project_name, output).await,
+            TaskCommand::Start(cmd) => start(&ctx, &cmd.name, output).await,
             TaskCommand::Stop(cmd) => {
-                stop(&ctx, &cmd.name, cmd.finished, cmd.cancelled, output_format).await
+                stop(&ctx, &cmd.name, cmd.finished, cmd.cancelled, output).await
             }
             TaskCommand::Edit(cmd) => {
                 edit(
                     &ctx,
-                    output_format,
+                    output,
                     &cmd.name,
                     cmd.new_name.as_deref(),
                     cmd.new_description.as_deref(),
+                    cmd.new_tags.as_deref(),
                 )
                 .await
             }
@@ -146,7 +156,8 @@ async fn create(
     description: Option<&str>,
     project_name: &str,
     start: bool,
-    output_format: OutputFormat,
+    tags: &[String],
+    output: &OutputOptions,
 ) -> miette::Result<()> {
     let txn = ctx
         .db
@@ -173,6 +184,7 @@ async fn create(
         description: Set(description.map(|d| d.to_string())),
         project_id: Set(project.id),
         status: Set(status),
+        tags: Set(tags_json(tags)),
         ..Default::default()
     }
     .save(&txn)
@@ -193,18 +205,18 @@ async fn create(
         .map_err(|e| miette::miette!("Failed to commit transaction: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message(format!(
             "Task '{}' created successfully with status '{}'",
             task.name, task.status
         ))
         .build()
-        .print();
+        .print()?;
 
     Ok(())
 }
 
-async fn remove(ctx: &Context, name: &str, output_format: OutputFormat) -> miette::Result<()> {
+async fn remove(ctx: &Context, name: &str, output: &OutputOptions) -> miette::Result<()> {
     let task = tasks::Entity::find()
         .filter(tasks::Column::Name.eq(name))
         .one(&ctx.db)
@@ -217,14 +229,14 @@ async fn remove(ctx: &Context, name: &str, output_format: OutputFormat) -> miett
         .map_err(|e| miette::miette!("Failed to remove task: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message("Task was removed successfully".to_string())
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
-async fn start(ctx: &Context, name: &str, _output_format: OutputFormat) -> miette::Result<()> {
+async fn start(ctx: &Context, name: &str, output: &OutputOptions) -> miette::Result<()> {
     let txn = ctx
         .db
         .begin()
@@ -249,10 +261,10 @@ async fn start(ctx: &Context, name: &str, _output_format: OutputFormat) -> miett
         .map_err(|e| miette::miette!("Failed to commit transaction: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(_output_format)
+        .with_options(output)
         .with_prefix_message(format!("Started working on task '{}'", task.name))
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
@@ -261,7 +273,7 @@ async fn stop(
     name: &str,
     finished: bool,
     cancelled: bool,
-    _output_format: OutputFormat,
+    output: &OutputOptions,
 ) -> miette::Result<()> {
     let txn = ctx
         .db
@@ -326,13 +338,13 @@ async fn stop(
         .map_err(|e| miette::miette!("Failed to commit transaction: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(_output_format)
+        .with_options(output)
         .with_prefix_message(format!(
             "Stopped working on task '{}'. New status: '{}'",
             task.name, task.status
         ))
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
@@ -346,11 +358,7 @@ fn calc_duration_to_now(start_time: DateTimeWithTimeZone) -> (i32, DateTimeWithT
     )
 }
 
-async fn list(
-    ctx: &Context,
-    project_name: &str,
-    output_format: OutputFormat,
-) -> miette::Result<()> {
+async fn list(ctx: &Context, project_name: &str, output: &OutputOptions) -> miette::Result<()> {
     let project = projects::Entity::find()
         .filter(projects::Column::Name.eq(project_name))
         .one(&ctx.db)
@@ -358,38 +366,52 @@ async fn list(
         .map_err(|e| miette::miette!("Failed to find project: {}", e))?
         .ok_or_else(|| miette::miette!("Project not found"))?;
 
-    let project_tasks = project
+    // Count the time entries of all tasks in a single grouped query
+    let tasks_table = project
         .find_related(tasks::Entity)
+        .select_only()
+        .columns([
+            tasks::Column::Id,
+            tasks::Column::Name,
+            tasks::Column::Description,
+            tasks::Column::Status,
+        ])
+        .column_as(time_entries::Column::Id.into_expr().count(), "time_entries")
+        .left_join(time_entries::Entity)
+        .group_by(tasks::Column::Id)
+        .order_by_asc(tasks::Column::Id)
+        .into_model::<TaskWithTimeEntryCount>()
         .all(&ctx.db)
         .await
-        .map_err(|e| miette::miette!("Failed to find tasks for project: {}", e))?;
-
-    let mut tasks_table: Vec<TaskTable> = vec![];
-
-    for task in &project_tasks {
-        let amount_of_time_entries = time_entries::Entity::find()
-            .filter(time_entries::Column::TaskId.eq(task.id))
-            .count(&ctx.db)
-            .await
-            .map_err(|e| miette::miette!("Failed to count time entries: {}", e))?;
-        tasks_table.push(TaskTable {
+        .map_err(|e| miette::miette!("Failed to find tasks for project: {}", e))?
+        .into_iter()
+        .map(|task| TaskTable {
             id: task.id,
-            name: task.name.clone(),
-            description: task.description.clone().unwrap_or_default(),
-            status: task.status.clone(),
-            time_entries: amount_of_time_entries,
-        });
-    }
+            name: task.name,
+            description: task.description.unwrap_or_default(),
+            status: task.status,
+            time_entries: task.time_entries as u64,
+        })
+        .collect::<Vec<_>>();
 
     CommandOutput::<Vec<TaskTable>, TaskTable>::builder()
         .with_table_rows(tasks_table)
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message(format!("Tasks for project '{}':", project.name))
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
+#[derive(FromQueryResult)]
+struct TaskWithTimeEntryCount {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    status: String,
+    time_entries: i64,
+}
+
 #[derive(Tabled, Serialize, Clone)]
 struct TaskTable {
     id: i32,
@@ -401,10 +423,11 @@ struct TaskTable {
 
 async fn edit(
     ctx: &Context,
-    output_format: OutputFormat,
+    output: &OutputOptions,
     name: &str,
     new_name: Option<&str>,
     new_description: Option<&str>,
+    new_tags: Option<&[String]>,
 ) -> miette::Result<()> {
     let mut task = tasks::Entity::find()
         .filter(tasks::Column::Name.eq(name))
@@ -420,6 +443,9 @@ async fn edit(
     if let Some(new_description) = new_description {
         task.description = Set(Some(new_description.to_string()));
     }
+    if let Some(new_tags) = new_tags {
+        task.tags = Set(tags_json(new_tags));
+    }
 
     let task = task
         .update(&ctx.db)
@@ -427,13 +453,23 @@ async fn edit(
         .map_err(|e| miette::miette!("Failed to update task: {}", e))?;
 
     CommandOutput::<Vec<NoTable>, NoTable>::builder()
-        .with_mode(output_format)
+        .with_options(output)
         .with_prefix_message(format!("Task '{}' updated successfully", task.name))
         .build()
-        .print();
+        .print()?;
     Ok(())
 }
 
+/// Tags of a task as stored in the database, a sorted JSON array without empty tags or duplicates
+fn tags_json(tags: &[String]) -> String {
+    let tags = tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+        .collect::<BTreeSet<_>>();
+    serde_json::to_string(&tags).expect("Failed to serialize tags")
+}
+
 async fn start_task(txn: &DatabaseTransaction, task_name: &str) -> miette::Result<tasks::Model> {
     // Find the task by name
     let mut task = tasks::Entity::find()
@@ -0,0 +1,187 @@
+use std::str::FromStr;
+
+use crate::duration::DurationFormat;
+
+/// Lightweight output template rendered once per table row.
+///
+/// Fields of the serialized row are referenced with `{field}` and can be piped
+/// through helpers, e.g. `{name}: {time_spent|hours}h`. Literal braces are
+/// written as `{{` and `}}`.
+#[derive(Debug, Clone)]
+pub(super) struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Field { name: String, helpers: Vec<Helper> },
+}
+
+/// Helpers that can be applied to a field, durations are expected in seconds
+#[derive(Debug, Clone, Copy)]
+enum Helper {
+    /// Decimal hours, e.g. `1.38`
+    Hours,
+    /// Whole minutes, e.g. `83`
+    Minutes,
+    /// Hours and minutes, e.g. `1h 23m`
+    Duration,
+    /// Clock style hours and minutes, e.g. `1:23`
+    Clock,
+    /// ISO 8601 duration, e.g. `PT1H23M`
+    Iso,
+    Upper,
+    Lower,
+}
+
+impl FromStr for Helper {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hours" => Ok(Helper::Hours),
+            "minutes" => Ok(Helper::Minutes),
+            "duration" => Ok(Helper::Duration),
+            "clock" => Ok(Helper::Clock),
+            "iso" => Ok(Helper::Iso),
+            "upper" => Ok(Helper::Upper),
+            "lower" => Ok(Helper::Lower),
+            _ => Err(miette::miette!(
+                "Unknown template helper '{}', available helpers: hours, minutes, duration, clock, iso, upper, lower",
+                s
+            )),
+        }
+    }
+}
+
+impl Template {
+    pub(super) fn parse(template: &str) -> miette::Result<Self> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut expression = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => expression.push(c),
+                            None => {
+                                return Err(miette::miette!(
+                                    "Unclosed '{{' in template: {}",
+                                    template
+                                ));
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Self::parse_field(&expression)?);
+                }
+                '}' => {
+                    return Err(miette::miette!(
+                        "Unexpected '}}' in template, use '}}}}' for a literal brace: {}",
+                        template
+                    ));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    fn parse_field(expression: &str) -> miette::Result<Segment> {
+        let mut parts = expression.split('|').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        if name.is_empty() {
+            return Err(miette::miette!("Empty field name in template"));
+        }
+        let helpers = parts
+            .map(Helper::from_str)
+            .collect::<miette::Result<Vec<_>>>()?;
+        Ok(Segment::Field {
+            name: name.to_string(),
+            helpers,
+        })
+    }
+
+    /// Render the template for a single serialized row
+    pub(super) fn render(&self, row: &serde_json::Value) -> miette::Result<String> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => rendered.push_str(literal),
+                Segment::Field { name, helpers } => {
+                    let value = row.get(name).ok_or_else(|| {
+                        miette::miette!(
+                            "Unknown template field '{}', available fields: {}",
+                            name,
+                            available_fields(row)
+                        )
+                    })?;
+                    let mut value = value.clone();
+                    for helper in helpers {
+                        value = helper.apply(name, &value)?;
+                    }
+                    rendered.push_str(&display_value(&value));
+                }
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+impl Helper {
+    fn apply(&self, field: &str, value: &serde_json::Value) -> miette::Result<serde_json::Value> {
+        let text = |value: String| Ok(serde_json::Value::String(value));
+        match self {
+            Helper::Upper => text(display_value(value).to_uppercase()),
+            Helper::Lower => text(display_value(value).to_lowercase()),
+            Helper::Hours => text(DurationFormat::Decimal.format(seconds(field, value)?)),
+            Helper::Minutes => text((seconds(field, value)? / 60).to_string()),
+            Helper::Duration => text(DurationFormat::Humanized.format(seconds(field, value)?)),
+            Helper::Clock => text(DurationFormat::Clock.format(seconds(field, value)?)),
+            Helper::Iso => text(DurationFormat::Iso8601.format(seconds(field, value)?)),
+        }
+    }
+}
+
+fn seconds(field: &str, value: &serde_json::Value) -> miette::Result<i64> {
+    value.as_i64().ok_or_else(|| {
+        miette::miette!(
+            "Template field '{}' is not a number of seconds: {}",
+            field,
+            value
+        )
+    })
+}
+
+fn display_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value.clone(),
+        serde_json::Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn available_fields(row: &serde_json::Value) -> String {
+    row.as_object()
+        .map(|object| object.keys().cloned().collect::<Vec<_>>().join(", "))
+        .unwrap_or_default()
+}
@@ -0,0 +1,525 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{duration::DurationFormat, rounding::RoundingPolicy};
+
+/// Environment variables overriding settings of the config file
+const ENV_OVERRIDES: [(&str, &str); 4] = [
+    ("CLOG_DATABASE_PATH", "database"),
+    ("CLOG_PROFILE", "profile"),
+    ("CLOG_SYNC_URL", "sync.url"),
+    ("CLOG_SYNC_PREFIX", "sync.prefix"),
+];
+
+/// Name of the profile which uses the settings of the config file without a profile
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+
+/// User configuration loaded from `config.toml` in the user config directory,
+/// with settings overridden by environment variables and `--set` flags
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// File the configuration was loaded from
+    #[serde(skip)]
+    pub(crate) path: PathBuf,
+    /// Where the settings come from, keyed by their dotted name, e.g. `sync.url`.
+    /// Settings which are missing have their default value.
+    #[serde(skip)]
+    pub(crate) sources: BTreeMap<String, ConfigSource>,
+    /// Path of the database, instead of `db.sqlite` in the user data directory
+    pub(crate) database: Option<PathBuf>,
+    /// Name of the active profile, whose settings override the ones of the config file
+    pub(crate) profile: Option<String>,
+    /// Profiles with their own database and sync settings, keyed by name
+    pub(crate) profiles: BTreeMap<String, ProfileConfig>,
+    pub(crate) output: OutputConfig,
+    /// Default rounding policy for billing
+    pub(crate) rounding: Option<RoundingPolicy>,
+    /// Targets for the total tracked time
+    pub(crate) goals: GoalsConfig,
+    /// Settings for individual projects, keyed by project name
+    pub(crate) projects: BTreeMap<String, ProjectConfig>,
+    pub(crate) sync: SyncConfig,
+    pub(crate) backup: BackupConfig,
+    pub(crate) team: TeamConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct OutputConfig {
+    /// Style used to display durations in human readable output
+    pub(crate) duration_format: Option<DurationFormat>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ProjectConfig {
+    /// Rounding policy overriding the default one for this project
+    pub(crate) rounding: Option<RoundingPolicy>,
+    /// Targets for the tracked time of this project
+    pub(crate) goals: GoalsConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct GoalsConfig {
+    /// Hours to track per day
+    pub(crate) daily_hours: Option<f64>,
+    /// Hours to track per ISO week
+    pub(crate) weekly_hours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct SyncConfig {
+    /// URL of the remote storage, e.g. `s3://bucket/clog` or `file:///mnt/share/clog`
+    pub(crate) url: Option<String>,
+    /// Path below the URL the remote files of this user are placed under, e.g. `alice/laptop`
+    pub(crate) prefix: Option<String>,
+    /// File the key for encrypting the remote database is derived from
+    pub(crate) key_file: Option<PathBuf>,
+    /// Number of previous uploads kept in the remote storage, 0 keeps none
+    pub(crate) keep_versions: usize,
+    /// Options of the storage client, e.g. `aws_access_key_id` or `azure_storage_account_name`
+    #[serde(deserialize_with = "deserialize_options")]
+    pub(crate) options: BTreeMap<String, String>,
+    /// Command printing the sync secrets, run like `<helper> get <name>`
+    pub(crate) credential_helper: Option<String>,
+    /// Whether to look up the sync secrets in the OS keyring
+    pub(crate) keyring: bool,
+    pub(crate) auto: AutoSyncConfig,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            prefix: None,
+            key_file: None,
+            keep_versions: 30,
+            options: BTreeMap::new(),
+            credential_helper: None,
+            keyring: true,
+            auto: AutoSyncConfig::default(),
+        }
+    }
+}
+
+/// Sync around the commands which change the local database
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct AutoSyncConfig {
+    /// Whether to pull before and push after the commands which change the local database
+    pub(crate) enabled: bool,
+    /// Minimum number of seconds between two checks of the remote storage
+    pub(crate) interval_secs: u64,
+    /// Number of seconds to wait for the remote storage before giving up
+    pub(crate) timeout_secs: u64,
+}
+
+impl Default for AutoSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 300,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Separate database and sync settings, e.g. for work and side projects
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ProfileConfig {
+    /// Path of the database, instead of `profiles/<name>.sqlite` in the user data directory
+    pub(crate) database: Option<PathBuf>,
+    /// Settings overriding the ones of the `[sync]` section, e.g. `url` or `prefix`
+    pub(crate) sync: toml::Table,
+}
+
+/// Teammates whose databases are combined in team reports
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct TeamConfig {
+    /// Sync prefixes of the teammates, relative to the sync URL, e.g. `bob/laptop`
+    pub(crate) members: Vec<String>,
+}
+
+/// Options of the storage clients are strings, but numbers and booleans like
+/// `allow_http = true` are accepted as well
+fn deserialize_options<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    BTreeMap::<String, toml::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, value)| match value {
+            toml::Value::String(value) => Ok((key, value)),
+            toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                Ok((key, value.to_string()))
+            }
+            value => Err(serde::de::Error::custom(format!(
+                "invalid type for sync option '{key}': {}, expected a string",
+                value.type_str()
+            ))),
+        })
+        .collect()
+}
+
+/// Retention policy for the automatic and manual backups of the database
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct BackupConfig {
+    /// Number of most recent backups to keep
+    pub(crate) keep_last: usize,
+    /// Number of days for which the newest backup of the day is kept
+    pub(crate) keep_daily: usize,
+    /// Number of ISO weeks for which the newest backup of the week is kept
+    pub(crate) keep_weekly: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration file, falling back to the defaults if it doesn't exist,
+    /// then apply the settings of the active profile, the environment variables and the
+    /// `--set` flags on top of it. The profile given on the command line takes precedence.
+    pub(crate) fn load(
+        path: &Path,
+        profile: Option<&str>,
+        cli_overrides: &[ConfigOverride],
+    ) -> miette::Result<Self> {
+        let mut table = read_table(path)?;
+        // Deserializing the file on its own points to the line of an invalid setting
+        toml::from_str::<Config>(&read_content(path)?).map_err(|e| {
+            miette::miette!(
+                help = "Fix or remove the setting, `clog config` still lists and changes the settings of the file",
+                "Invalid config file {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        let mut sources = flatten(&toml::Value::Table(table.clone()))
+            .into_iter()
+            .map(|(key, _)| (key, ConfigSource::File))
+            .collect::<BTreeMap<_, _>>();
+        let mut overrides = vec![];
+        for (variable, key) in ENV_OVERRIDES {
+            if let Ok(value) = std::env::var(variable)
+                && !value.is_empty()
+            {
+                overrides.push((
+                    key.to_string(),
+                    toml::Value::String(value),
+                    ConfigSource::Env(variable),
+                ));
+            }
+        }
+        for cli_override in cli_overrides {
+            overrides.push((
+                cli_override.key.clone(),
+                cli_override.value.clone(),
+                ConfigSource::Cli,
+            ));
+        }
+        if let Some(profile) = profile {
+            overrides.push((
+                "profile".to_string(),
+                toml::Value::String(profile.to_string()),
+                ConfigSource::Cli,
+            ));
+        }
+
+        // The profile is selected before its settings are applied below the overrides
+        let explicit_profile = overrides.iter().any(|(key, _, _)| key == "profile");
+        let active_profile = overrides
+            .iter()
+            .rev()
+            .find(|(key, _, _)| key == "profile")
+            .map(|(_, value, _)| value)
+            .or_else(|| table.get("profile"))
+            .and_then(|value| value.as_str())
+            .filter(|name| *name != DEFAULT_PROFILE)
+            .map(str::to_string);
+        if let Some(name) = active_profile {
+            let profile_table = table
+                .get("profiles")
+                .and_then(|profiles| profiles.get(&name))
+                .cloned()
+                .ok_or_else(|| {
+                    miette::miette!(
+                        help = format!(
+                            "Create it with `clog profile create {name}`, or use the settings without a profile with `--profile {DEFAULT_PROFILE}`"
+                        ),
+                        "Profile '{}' doesn't exist",
+                        name
+                    )
+                })?;
+            // A profile selected for the command uses its own database instead of the default one
+            if explicit_profile && profile_table.get("database").is_some() {
+                overrides
+                    .retain(|(_, _, source)| *source != ConfigSource::Env("CLOG_DATABASE_PATH"));
+            }
+            for (key, value) in flatten(&profile_table) {
+                set_value(&mut table, &key, value)?;
+                sources.insert(key, ConfigSource::Profile);
+            }
+        }
+        for (key, value, source) in overrides {
+            set_value(&mut table, &key, value)?;
+            sources.insert(key, source);
+        }
+
+        let mut config: Config = toml::Value::Table(table).try_into().map_err(|e| {
+            miette::miette!(
+                help = "Check the config file and the settings of the environment variables and `--set` flags",
+                "Invalid config {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        config.path = path.to_path_buf();
+        config.sources = sources;
+        Ok(config)
+    }
+
+    /// Effective settings as pairs of their dotted name and value, sorted by name
+    pub(crate) fn settings(&self) -> miette::Result<Vec<(String, toml::Value)>> {
+        let value = toml::Value::try_from(self)
+            .map_err(|e| miette::miette!("Failed to serialize config: {}", e))?;
+        Ok(flatten(&value))
+    }
+
+    /// Where the effective value of a setting comes from
+    pub(crate) fn source(&self, key: &str) -> ConfigSource {
+        self.sources
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
+
+    /// Name of the active profile, `None` without a profile
+    pub(crate) fn active_profile(&self) -> Option<&str> {
+        self.profile
+            .as_deref()
+            .filter(|profile| *profile != DEFAULT_PROFILE)
+    }
+
+    /// Rounding policy of a project, falling back to the default policy
+    pub(crate) fn rounding_for(&self, project_name: &str) -> Option<&RoundingPolicy> {
+        self.projects
+            .get(project_name)
+            .and_then(|project| project.rounding.as_ref())
+            .or(self.rounding.as_ref())
+    }
+}
+
+/// Where the effective value of a setting comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConfigSource {
+    Default,
+    File,
+    Profile,
+    Env(&'static str),
+    Cli,
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File => write!(f, "file"),
+            ConfigSource::Profile => write!(f, "profile"),
+            ConfigSource::Env(variable) => write!(f, "env {variable}"),
+            ConfigSource::Cli => write!(f, "--set"),
+        }
+    }
+}
+
+/// Setting overridden on the command line, e.g. `sync.url=file:///mnt/share/clog`
+#[derive(Debug, Clone)]
+pub(crate) struct ConfigOverride {
+    key: String,
+    value: toml::Value,
+}
+
+impl FromStr for ConfigOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got '{s}'"))?;
+        Ok(Self {
+            key: key.trim().to_string(),
+            value: parse_value(value.trim()),
+        })
+    }
+}
+
+/// Parse the value of a setting given on the command line as TOML, e.g. `30`, `true` or
+/// `["alice", "bob"]`, falling back to a string for unquoted text
+pub(crate) fn parse_value(value: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// Set a setting in the config file to a value parsed as TOML, falling back to a string
+pub(crate) fn set_in_file(path: &Path, key: &str, value: &str) -> miette::Result<()> {
+    let value = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(value));
+    write_setting(path, key, value)
+}
+
+/// Set a setting in the config file, keeping the comments and formatting of the other
+/// settings. The file is only written if the changed config is valid.
+pub(crate) fn write_setting(path: &Path, key: &str, value: toml_edit::Value) -> miette::Result<()> {
+    let content = read_content(path)?;
+    let mut document = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| miette::miette!("Invalid config file {}: {}", path.display(), e))?;
+    insert_setting(&mut document, key, value.clone())?;
+
+    let changed_content = document.to_string();
+    if let Err(e) = toml::from_str::<Config>(&changed_content) {
+        // A file which is invalid already can still be changed if the setting is valid on its
+        // own, so the settings can be fixed one by one
+        let mut setting_document = toml_edit::DocumentMut::new();
+        insert_setting(&mut setting_document, key, value)?;
+        let setting_error = match toml::from_str::<Config>(&content) {
+            Ok(_) => Some(e),
+            Err(_) => toml::from_str::<Config>(&setting_document.to_string()).err(),
+        };
+        if let Some(e) = setting_error {
+            return Err(miette::miette!(
+                help = "List the available settings with `clog config list`",
+                "Invalid setting '{}': {}",
+                key,
+                e
+            ));
+        }
+    }
+    if let Some(config_dir) = path.parent() {
+        std::fs::create_dir_all(config_dir).map_err(|e| {
+            miette::miette!(
+                "Failed to create config directory {}: {}",
+                config_dir.display(),
+                e
+            )
+        })?;
+    }
+    std::fs::write(path, changed_content)
+        .map_err(|e| miette::miette!("Failed to write config file {}: {}", path.display(), e))
+}
+
+/// Insert a value at a dotted key, creating the tables on the way
+fn insert_setting(
+    document: &mut toml_edit::DocumentMut,
+    key: &str,
+    value: toml_edit::Value,
+) -> miette::Result<()> {
+    let mut parts = key.split('.').collect::<Vec<_>>();
+    let last = parts
+        .pop()
+        .filter(|last| !last.is_empty())
+        .ok_or_else(|| miette::miette!("Invalid setting '{}'", key))?;
+    let mut table = document.as_table_mut();
+    for part in parts {
+        let item = table.entry(part).or_insert_with(|| {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            toml_edit::Item::Table(table)
+        });
+        table = item.as_table_mut().ok_or_else(|| {
+            miette::miette!("Invalid setting '{}', '{}' is not a table", key, part)
+        })?;
+    }
+    table.insert(last, toml_edit::value(value));
+    Ok(())
+}
+
+/// Leaf values of nested tables as pairs of their dotted name and value
+fn flatten(value: &toml::Value) -> Vec<(String, toml::Value)> {
+    let mut settings = vec![];
+    if let toml::Value::Table(table) = value {
+        for (key, value) in table {
+            match value {
+                toml::Value::Table(_) => settings.extend(
+                    flatten(value)
+                        .into_iter()
+                        .map(|(subkey, value)| (format!("{key}.{subkey}"), value)),
+                ),
+                value => settings.push((key.clone(), value.clone())),
+            }
+        }
+    }
+    settings
+}
+
+/// Settings of the config file as pairs of their dotted name and value, without checking
+/// whether they are valid
+pub(crate) fn file_settings(path: &Path) -> miette::Result<Vec<(String, toml::Value)>> {
+    Ok(flatten(&toml::Value::Table(read_table(path)?)))
+}
+
+/// Raw content of the config file, empty if it doesn't exist
+fn read_content(path: &Path) -> miette::Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    std::fs::read_to_string(path)
+        .map_err(|e| miette::miette!("Failed to read config file {}: {}", path.display(), e))
+}
+
+/// Raw settings of the config file, empty if it doesn't exist
+fn read_table(path: &Path) -> miette::Result<toml::Table> {
+    toml::from_str(&read_content(path)?)
+        .map_err(|e| miette::miette!("Invalid config file {}: {}", path.display(), e))
+}
+
+/// Set a value at a dotted key like `sync.url`, creating the tables on the way
+fn set_value(table: &mut toml::Table, key: &str, value: toml::Value) -> miette::Result<()> {
+    let mut parts = key.split('.').collect::<Vec<_>>();
+    let last = parts
+        .pop()
+        .filter(|last| !last.is_empty())
+        .ok_or_else(|| miette::miette!("Invalid setting '{}'", key))?;
+    let mut table = table;
+    for part in parts {
+        table = table
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| {
+                miette::miette!("Invalid setting '{}', '{}' is not a table", key, part)
+            })?;
+    }
+    table.insert(last.to_string(), value);
+    Ok(())
+}
+
+/// Default location of the config file in the user config directory,
+/// used unless `--config` or the `CLOG_CONFIG` environment variable are set
+pub(crate) fn config_path() -> miette::Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .ok_or_else(|| miette::miette!("Failed to get user config directory"))?
+        .join(env!("CARGO_PKG_NAME"))
+        .join("config.toml"))
+}
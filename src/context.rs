@@ -1,9 +1,12 @@
+use crate::config::Config;
+
 pub(crate) struct Context {
     pub(crate) db: sea_orm::DatabaseConnection,
+    pub(crate) config: Config,
 }
 
 impl Context {
-    pub fn new(db: sea_orm::DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: sea_orm::DatabaseConnection, config: Config) -> Self {
+        Self { db, config }
     }
 }
@@ -1,15 +1,229 @@
-use std::path::PathBuf;
-
-pub(crate) fn db_path() -> miette::Result<PathBuf> {
-    let db_path = if let Ok(path) = std::env::var("CLOG_DATABASE_PATH") {
-        PathBuf::from(path)
-    } else {
-        let user_database_dir = dirs::data_local_dir()
-            .ok_or_else(|| miette::miette!("Failed to get local data directory"))?
-            .join(env!("CARGO_PKG_NAME"));
-        std::fs::create_dir_all(&user_database_dir)
-            .map_err(|e| miette::miette!("Failed to create user database directory: {}", e))?;
-        user_database_dir.join("db.sqlite")
-    };
-    Ok(db_path)
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use migration::MigratorTrait;
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Statement};
+
+use crate::{
+    backup::{self, BackupReason},
+    config::Config,
+};
+
+/// Path of the local database, set by the `database` setting of the active profile or the
+/// config file, or `CLOG_DATABASE_PATH`
+pub(crate) fn db_path(config: &Config) -> miette::Result<PathBuf> {
+    match &config.database {
+        Some(path) => Ok(path.clone()),
+        None => default_db_path(config.active_profile()),
+    }
+}
+
+/// Path of the database in the user data directory, `db.sqlite` without a profile and
+/// `profiles/<name>.sqlite` for a profile
+pub(crate) fn default_db_path(profile: Option<&str>) -> miette::Result<PathBuf> {
+    let mut user_database_dir = dirs::data_local_dir()
+        .ok_or_else(|| miette::miette!("Failed to get local data directory"))?
+        .join(env!("CARGO_PKG_NAME"));
+    if profile.is_some() {
+        user_database_dir.push("profiles");
+    }
+    std::fs::create_dir_all(&user_database_dir)
+        .map_err(|e| miette::miette!("Failed to create user database directory: {}", e))?;
+    Ok(match profile {
+        Some(profile) => user_database_dir.join(format!("{profile}.sqlite")),
+        None => user_database_dir.join("db.sqlite"),
+    })
+}
+
+/// Open the local database, creating it if it doesn't exist, and migrate it to the current
+/// schema after backing it up
+pub(crate) async fn connect(config: &Config) -> miette::Result<DatabaseConnection> {
+    let db_path =
+        db_path(config).map_err(|e| miette::miette!("Failed to get database path: {}", e))?;
+    let connection_string = format!(
+        "sqlite://{}?mode=rwc",
+        db_path
+            .to_str()
+            .ok_or_else(|| miette::miette!("Invalid database path: {}", db_path.display()))?
+    );
+    let db = Database::connect(connection_string)
+        .await
+        .map_err(|e| miette::miette!("Failed to connect to database: {}", e))?;
+
+    // Keep a copy of an existing database in case a migration breaks it
+    let pending_migrations = migration::Migrator::get_pending_migrations(&db)
+        .await
+        .map_err(|e| miette::miette!("Failed to check pending migrations: {}", e))?;
+    let applied_migrations = migration::Migrator::get_applied_migrations(&db)
+        .await
+        .map_err(|e| miette::miette!("Failed to check applied migrations: {}", e))?;
+    if !pending_migrations.is_empty() && !applied_migrations.is_empty() {
+        backup::create(&db, &db_path, BackupReason::Migration, &config.backup).await?;
+    }
+
+    migration::Migrator::up(&db, None)
+        .await
+        .map_err(|e| miette::miette!("Failed to run migrations: {}", e))?;
+    Ok(db)
+}
+
+/// Path of a file next to the database, e.g. `db.sqlite.sync-state.json`
+pub(crate) fn sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = db_path.as_os_str().to_owned();
+    file_name.push(suffix);
+    PathBuf::from(file_name)
+}
+
+/// Write a consistent copy of the database to a new file with `VACUUM INTO`,
+/// which is safe while the database is in use, unlike copying the database file
+pub(crate) async fn vacuum_into(db: &DatabaseConnection, target_path: &Path) -> miette::Result<()> {
+    let escaped_path = target_path.to_string_lossy().replace('\'', "''");
+    db.execute_unprepared(&format!("VACUUM INTO '{escaped_path}'"))
+        .await
+        .map_err(|e| {
+            miette::miette!(
+                "Failed to copy the database to {}: {}",
+                target_path.display(),
+                e
+            )
+        })?;
+    Ok(())
+}
+
+/// Take a consistent copy of the database and read it into memory
+pub(crate) async fn snapshot(db: &DatabaseConnection, db_path: &Path) -> miette::Result<Vec<u8>> {
+    let snapshot_path = sidecar_path(db_path, ".snapshot.sqlite");
+    // `VACUUM INTO` refuses to overwrite an existing file
+    remove_if_exists(&snapshot_path).await?;
+    vacuum_into(db, &snapshot_path).await?;
+    let content = tokio::fs::read(&snapshot_path).await.map_err(|e| {
+        miette::miette!(
+            "Failed to read database snapshot at {}: {}",
+            snapshot_path.display(),
+            e
+        )
+    });
+    remove_if_exists(&snapshot_path).await?;
+    content
+}
+
+/// Open a database file after checking that it is intact and not created by a newer
+/// version of clog, then migrate it to the current schema
+pub(crate) async fn open_validated(path: &Path) -> miette::Result<DatabaseConnection> {
+    let db = Database::connect(format!("sqlite://{}?mode=rw", path.display()))
+        .await
+        .map_err(|e| miette::miette!("Failed to open database {}: {}", path.display(), e))?;
+
+    let integrity_check = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            "PRAGMA integrity_check",
+        ))
+        .await
+        .map_err(|e| miette::miette!("The database {} is corrupted: {}", path.display(), e))?
+        .map(|row| row.try_get_by_index::<String>(0))
+        .transpose()
+        .map_err(|e| miette::miette!("Failed to check database {}: {}", path.display(), e))?;
+    if integrity_check.as_deref() != Some("ok") {
+        return Err(miette::miette!(
+            "The database {} is corrupted: {}",
+            path.display(),
+            integrity_check.unwrap_or_default()
+        ));
+    }
+
+    let known_migrations = migration::Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect::<HashSet<_>>();
+    let unknown_migrations = db
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT version FROM seaql_migrations",
+        ))
+        .await
+        .map_err(|e| {
+            miette::miette!(
+                "The database {} is not a clog database: {}",
+                path.display(),
+                e
+            )
+        })?
+        .into_iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .filter(|version| !known_migrations.contains(version))
+        .collect::<Vec<_>>();
+    if !unknown_migrations.is_empty() {
+        return Err(miette::miette!(
+            help = "Update clog to sync with this database",
+            "The database {} was created by a newer version of clog (unknown migrations: {})",
+            path.display(),
+            unknown_migrations.join(", ")
+        ));
+    }
+
+    migration::Migrator::up(&db, None)
+        .await
+        .map_err(|e| miette::miette!("Failed to migrate database {}: {}", path.display(), e))?;
+    Ok(db)
+}
+
+/// Open a database file without writing to it, e.g. the imported database of a teammate.
+/// Databases of older or newer versions of clog are rejected, as they can't be migrated
+/// while opened read-only.
+pub(crate) async fn open_read_only(path: &Path) -> miette::Result<DatabaseConnection> {
+    let db = Database::connect(format!("sqlite://{}?mode=ro", path.display()))
+        .await
+        .map_err(|e| miette::miette!("Failed to open database {}: {}", path.display(), e))?;
+    let pending_migrations = migration::Migrator::get_pending_migrations(&db)
+        .await
+        .map_err(|e| {
+            miette::miette!(
+                help = "Update clog to read this database",
+                "Failed to check the migrations of database {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+    if !pending_migrations.is_empty() {
+        return Err(miette::miette!(
+            help = "Import the database again or open it once with this version of clog",
+            "The database {} was created by an older version of clog",
+            path.display()
+        ));
+    }
+    Ok(db)
+}
+
+/// Replace the local database with a validated database file by renaming it,
+/// so the local database is never left partially written.
+/// The connection to the local database is closed first.
+pub(crate) async fn replace(
+    db: DatabaseConnection,
+    db_path: &Path,
+    new_path: &Path,
+) -> miette::Result<()> {
+    db.close()
+        .await
+        .map_err(|e| miette::miette!("Failed to close the database: {}", e))?;
+    tokio::fs::rename(new_path, db_path).await.map_err(|e| {
+        miette::miette!(
+            "Failed to replace database file at {}: {}",
+            db_path.display(),
+            e
+        )
+    })
+}
+
+pub(crate) async fn remove_if_exists(path: &Path) -> miette::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(miette::miette!(
+            "Failed to remove file {}: {}",
+            path.display(),
+            e
+        )),
+        _ => Ok(()),
+    }
 }
@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Style used to display durations in human readable output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DurationFormat {
+    /// Hours and minutes, e.g. `1h 23m`
+    #[default]
+    Humanized,
+    /// Clock style hours and minutes, e.g. `1:23`
+    Clock,
+    /// Decimal hours, e.g. `1.38`
+    Decimal,
+    /// ISO 8601 duration, e.g. `PT1H23M`
+    Iso8601,
+}
+
+impl DurationFormat {
+    pub(crate) fn format(&self, secs: i64) -> String {
+        let sign = if secs < 0 { "-" } else { "" };
+        let abs_secs = secs.abs();
+        let (hours, mins, secs) = (abs_secs / 3600, (abs_secs % 3600) / 60, abs_secs % 60);
+        match self {
+            DurationFormat::Humanized => format!("{sign}{hours}h {mins:02}m"),
+            DurationFormat::Clock => format!("{sign}{hours}:{mins:02}"),
+            DurationFormat::Decimal => format!("{sign}{:.2}", abs_secs as f64 / 3600.0),
+            DurationFormat::Iso8601 => {
+                let mut iso = format!("{sign}PT");
+                if hours > 0 {
+                    iso.push_str(&format!("{hours}H"));
+                }
+                if mins > 0 {
+                    iso.push_str(&format!("{mins}M"));
+                }
+                if secs > 0 || (hours == 0 && mins == 0) {
+                    iso.push_str(&format!("{secs}S"));
+                }
+                iso
+            }
+        }
+    }
+}
+
+/// A duration in seconds which is displayed in the selected format,
+/// but always serialized as the raw amount of seconds for machine readable output
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Duration {
+    secs: i64,
+    format: DurationFormat,
+}
+
+impl Duration {
+    pub(crate) fn new(secs: i64, format: DurationFormat) -> Self {
+        Self { secs, format }
+    }
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format.format(self.secs))
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_i64(self.secs)
+    }
+}
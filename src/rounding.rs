@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, num::NonZeroU32};
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::time_entries;
+
+/// Rounding policy applied to tracked time for billing,
+/// the stored durations of the time entries are never modified
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RoundingPolicy {
+    /// Increment in minutes to round to, e.g. 6 or 15
+    increment_mins: NonZeroU32,
+    #[serde(default)]
+    mode: RoundingMode,
+    #[serde(default)]
+    scope: RoundingScope,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RoundingMode {
+    #[default]
+    Up,
+    Down,
+    Nearest,
+}
+
+/// What the rounding is applied to
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RoundingScope {
+    /// Round every time entry on its own
+    #[default]
+    Entry,
+    /// Round the total of every local day
+    Day,
+    /// Round the total of the aggregated entries
+    Total,
+}
+
+impl RoundingPolicy {
+    /// Round an amount of seconds to the increment of the policy
+    pub(crate) fn round(&self, secs: i64) -> i64 {
+        let increment = i64::from(self.increment_mins.get()) * 60;
+        let rounded_down = secs.div_euclid(increment) * increment;
+        let remainder = secs - rounded_down;
+        match self.mode {
+            RoundingMode::Down => rounded_down,
+            RoundingMode::Up if remainder > 0 => rounded_down + increment,
+            RoundingMode::Up => rounded_down,
+            RoundingMode::Nearest if remainder * 2 >= increment => rounded_down + increment,
+            RoundingMode::Nearest => rounded_down,
+        }
+    }
+
+    /// Sum the durations of the time entries with the rounding of the policy applied
+    pub(crate) fn apply<'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a time_entries::Model>,
+    ) -> i64 {
+        match self.scope {
+            RoundingScope::Entry => entries
+                .into_iter()
+                .map(|entry| self.round(i64::from(entry.duration)))
+                .sum(),
+            RoundingScope::Day => {
+                let mut days: BTreeMap<chrono::NaiveDate, i64> = BTreeMap::new();
+                for entry in entries {
+                    *days
+                        .entry(entry.start_time.with_timezone(&chrono::Local).date_naive())
+                        .or_default() += i64::from(entry.duration);
+                }
+                days.into_values().map(|secs| self.round(secs)).sum()
+            }
+            RoundingScope::Total => self.round(
+                entries
+                    .into_iter()
+                    .map(|entry| i64::from(entry.duration))
+                    .sum(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDateTime, TimeDelta};
+
+    use super::*;
+
+    fn policy(increment_mins: u32, mode: RoundingMode, scope: RoundingScope) -> RoundingPolicy {
+        RoundingPolicy {
+            increment_mins: NonZeroU32::new(increment_mins).unwrap(),
+            mode,
+            scope,
+        }
+    }
+
+    /// Time entry starting at a local time, so the days don't depend on the time zone
+    fn entry(start_time: &str, duration_mins: i32) -> time_entries::Model {
+        let start_time = NaiveDateTime::parse_from_str(start_time, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+            .fixed_offset();
+        time_entries::Model {
+            id: 0,
+            task_id: 0,
+            start_time,
+            end_time: Some(start_time + TimeDelta::minutes(i64::from(duration_mins))),
+            duration: duration_mins * 60,
+            created_at: start_time,
+            updated_at: start_time,
+            uuid: String::new(),
+        }
+    }
+
+    fn entries() -> Vec<time_entries::Model> {
+        vec![
+            entry("2025-03-05 09:00", 7),
+            entry("2025-03-05 10:00", 7),
+            entry("2025-03-06 09:00", 15),
+            entry("2025-03-07 09:00", 0),
+        ]
+    }
+
+    #[test]
+    fn round_up() {
+        let policy = policy(15, RoundingMode::Up, RoundingScope::Entry);
+        assert_eq!(policy.round(0), 0);
+        assert_eq!(policy.round(1), 900);
+        assert_eq!(policy.round(900), 900);
+        assert_eq!(policy.round(901), 1800);
+    }
+
+    #[test]
+    fn round_down() {
+        let policy = policy(15, RoundingMode::Down, RoundingScope::Entry);
+        assert_eq!(policy.round(0), 0);
+        assert_eq!(policy.round(899), 0);
+        assert_eq!(policy.round(900), 900);
+        assert_eq!(policy.round(1799), 900);
+    }
+
+    #[test]
+    fn round_nearest() {
+        let policy = policy(15, RoundingMode::Nearest, RoundingScope::Entry);
+        assert_eq!(policy.round(0), 0);
+        assert_eq!(policy.round(449), 0);
+        assert_eq!(policy.round(450), 900);
+        assert_eq!(policy.round(900), 900);
+        assert_eq!(policy.round(1349), 900);
+        assert_eq!(policy.round(1350), 1800);
+    }
+
+    #[test]
+    fn apply_per_entry() {
+        // 7 + 7 + 15 + 0 minutes
+        let up = policy(15, RoundingMode::Up, RoundingScope::Entry);
+        assert_eq!(up.apply(&entries()), (15 + 15 + 15) * 60);
+        let down = policy(15, RoundingMode::Down, RoundingScope::Entry);
+        assert_eq!(down.apply(&entries()), 15 * 60);
+        let nearest = policy(15, RoundingMode::Nearest, RoundingScope::Entry);
+        assert_eq!(nearest.apply(&entries()), 15 * 60);
+    }
+
+    #[test]
+    fn apply_per_day() {
+        // 14, 15 and 0 minutes per day
+        let up = policy(15, RoundingMode::Up, RoundingScope::Day);
+        assert_eq!(up.apply(&entries()), (15 + 15) * 60);
+        let down = policy(15, RoundingMode::Down, RoundingScope::Day);
+        assert_eq!(down.apply(&entries()), 15 * 60);
+        let nearest = policy(15, RoundingMode::Nearest, RoundingScope::Day);
+        assert_eq!(nearest.apply(&entries()), (15 + 15) * 60);
+    }
+
+    #[test]
+    fn apply_to_total() {
+        // 29 minutes in total
+        let up = policy(15, RoundingMode::Up, RoundingScope::Total);
+        assert_eq!(up.apply(&entries()), 30 * 60);
+        let down = policy(15, RoundingMode::Down, RoundingScope::Total);
+        assert_eq!(down.apply(&entries()), 15 * 60);
+        let nearest = policy(15, RoundingMode::Nearest, RoundingScope::Total);
+        assert_eq!(nearest.apply(&entries()), 30 * 60);
+    }
+
+    #[test]
+    fn apply_without_entries() {
+        for scope in [
+            RoundingScope::Entry,
+            RoundingScope::Day,
+            RoundingScope::Total,
+        ] {
+            let policy = policy(15, RoundingMode::Up, scope);
+            assert_eq!(policy.apply(&[]), 0);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+use crate::{config::Config, db};
+
+const TEAM_DIR_SUFFIX: &str = ".team";
+const IMPORT_FILE_EXTENSION: &str = "sqlite";
+/// Name of the local database in team reports if no sync prefix is configured
+const LOCAL_MEMBER_NAME: &str = "local";
+
+/// Directory next to the database with the read-only copies of the teammates' databases
+pub(crate) fn import_dir(db_path: &Path) -> PathBuf {
+    db::sidecar_path(db_path, TEAM_DIR_SUFFIX)
+}
+
+/// Path of the imported database of a teammate, named after their sync prefix
+pub(crate) fn import_path(db_path: &Path, member: &str) -> PathBuf {
+    import_dir(db_path).join(format!("{}.{IMPORT_FILE_EXTENSION}", member_name(member)))
+}
+
+/// Name of a teammate in team reports, derived from their sync prefix
+fn member_name(prefix: &str) -> String {
+    prefix.trim_matches('/').replace('/', "_")
+}
+
+/// Name of the local database in team reports, the sync prefix if configured
+pub(crate) fn local_member_name(config: &Config) -> String {
+    config
+        .sync
+        .prefix
+        .as_deref()
+        .map(member_name)
+        .unwrap_or_else(|| LOCAL_MEMBER_NAME.to_string())
+}
+
+/// Imported databases of the teammates as pairs of the file name without extension,
+/// which identifies the teammate, and the path, sorted by name
+pub(crate) async fn imported_members(db_path: &Path) -> miette::Result<Vec<(String, PathBuf)>> {
+    let import_dir = import_dir(db_path);
+    let mut entries = match tokio::fs::read_dir(&import_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => {
+            return Err(miette::miette!(
+                "Failed to read team directory {}: {}",
+                import_dir.display(),
+                e
+            ));
+        }
+    };
+    let mut members = vec![];
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| miette::miette!("Failed to read team directory entry: {}", e))?
+    {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_none_or(|extension| extension != IMPORT_FILE_EXTENSION)
+        {
+            continue;
+        }
+        if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+            members.push((name.to_string(), path.clone()));
+        }
+    }
+    members.sort();
+    Ok(members)
+}
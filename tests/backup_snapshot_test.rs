@@ -0,0 +1,51 @@
+mod common;
+
+#[test]
+fn backup_restore_snapshot() {
+    let db_path = common::setup_test_db("backup_restore", 0);
+    let mut output = String::new();
+    output += &common::run_command(
+        &["run", "--", "project", "create", "-n", "backed_up_project"],
+        &db_path,
+    );
+    // Backup names contain the time of the backup, so only the reasons are compared
+    common::run_command(&["run", "--", "backup", "create"], &db_path);
+    output += &common::run_command(
+        &["run", "--", "project", "remove", "-n", "backed_up_project"],
+        &db_path,
+    );
+    common::run_command(&["run", "--", "backup", "restore"], &db_path);
+    output += &common::run_command(&["run", "--", "project", "list"], &db_path);
+    output += &common::run_command(
+        &["run", "--", "backup", "list", "--columns", "reason"],
+        &db_path,
+    );
+
+    common::assert_snapshot("backup_restore", &output);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn backup_keep_nothing_snapshot() {
+    let db_path = common::setup_test_db("backup_keep_nothing", 1);
+    std::fs::write(
+        common::config_path(&db_path),
+        "[sync]\nkeyring = false\n\n[backup]\nkeep_last = 0\nkeep_daily = 0\nkeep_weekly = 0\n",
+    )
+    .expect("Failed to write config file");
+    common::run_command(&["run", "--", "backup", "create"], &db_path);
+    common::run_command(&["run", "--", "backup", "create"], &db_path);
+    // Only the newest backup is left, which can still be restored
+    let mut output = common::run_command(
+        &["run", "--", "backup", "list", "--columns", "reason"],
+        &db_path,
+    );
+    common::run_command(&["run", "--", "backup", "restore"], &db_path);
+    output += &common::run_command(
+        &["run", "--", "backup", "list", "--columns", "reason"],
+        &db_path,
+    );
+
+    common::assert_snapshot("backup_keep_nothing", &output);
+    common::reset_sqlite_db(&db_path);
+}
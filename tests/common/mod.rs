@@ -1,5 +1,7 @@
 use std::path::{Path, PathBuf};
 
+use sea_orm::{ConnectionTrait, Database};
+
 pub fn get_snapshot_path(name: &str) -> String {
     format!("tests/snapshots/{}.txt", name)
 }
@@ -16,15 +18,60 @@ pub fn write_snapshot(name: &str, content: &str) {
 
 pub fn reset_sqlite_db(db_path: &Path) {
     if db_path.exists() {
-        std::fs::remove_file(&db_path).expect("Failed to remove database file");
+        std::fs::remove_file(db_path).expect("Failed to remove database file");
+    }
+    for suffix in [
+        ".sync-state.json",
+        ".sync-base.sqlite",
+        ".auto-sync.json",
+        ".config.toml",
+    ] {
+        let sync_file_path = format!("{}{suffix}", db_path.display());
+        if Path::new(&sync_file_path).exists() {
+            std::fs::remove_file(sync_file_path).expect("Failed to remove sync file");
+        }
+    }
+    for suffix in [".backups", ".team"] {
+        let dir_path = format!("{}{suffix}", db_path.display());
+        if Path::new(&dir_path).exists() {
+            std::fs::remove_dir_all(dir_path).expect("Failed to remove sidecar directory");
+        }
     }
     println!("SQLite database reset at: {}", db_path.display());
 }
 
 pub fn run_command(args: &[&str], db_path: &Path) -> String {
-    let output = std::process::Command::new("cargo")
+    run_command_with_env(args, db_path, &[])
+}
+
+/// Environment variables of the developer running the tests which would change the output
+/// or sync the test databases with a real remote storage
+const DEVELOPER_ENV_VARS: [&str; 6] = [
+    "CLOG_PROFILE",
+    "CLOG_SYNC_URL",
+    "CLOG_SYNC_PREFIX",
+    "CLOG_SYNC_PASSPHRASE",
+    "CLOG_STORAGE_ACCOUNT_NAME",
+    "CLOG_STORAGE_ACCESS_KEY",
+];
+
+/// Config file of a test, instead of the one of the developer running the tests
+pub fn config_path(db_path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.config.toml", db_path.display()))
+}
+
+pub fn run_command_with_env(args: &[&str], db_path: &Path, envs: &[(&str, &str)]) -> String {
+    let mut command = std::process::Command::new("cargo");
+    for variable in DEVELOPER_ENV_VARS {
+        command.env_remove(variable);
+    }
+    let output = command
         .args(args)
         .env("CLOG_DATABASE_PATH", db_path)
+        .env("CLOG_CONFIG", config_path(db_path))
+        // The days of the reports are local days
+        .env("TZ", "UTC")
+        .envs(envs.iter().copied())
         .output()
         .expect("Failed to execute command");
 
@@ -38,9 +85,81 @@ pub fn setup_test_db(test_name: &str, index: usize) -> PathBuf {
     std::fs::create_dir_all(db_path.clone().parent().unwrap())
         .expect("Failed to create database directory");
     std::fs::File::create(&db_path).expect("Failed to create database file");
+    // The secrets in the keyring of the developer must not be used either
+    std::fs::write(config_path(&db_path), "[sync]\nkeyring = false\n")
+        .expect("Failed to write config file");
     db_path
 }
 
+/// Insert a finished time entry of a task, as the commands only track time entries live
+#[allow(dead_code)] // Only used by some of the tests
+pub fn insert_time_entry(db_path: &Path, task_name: &str, start_time: &str, duration_mins: i64) {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+    runtime.block_on(async {
+        let db = Database::connect(format!("sqlite://{}", db_path.display()))
+            .await
+            .expect("Failed to connect to test database");
+        db.execute_unprepared(&format!(
+            "INSERT INTO time_entries (task_id, start_time, end_time, duration, uuid)
+             SELECT id, '{start_time}', datetime('{start_time}', '+{duration_mins} minutes'),
+                 {duration_mins} * 60, lower(hex(randomblob(16)))
+             FROM tasks WHERE name = '{task_name}'"
+        ))
+        .await
+        .expect("Failed to insert time entry");
+    });
+}
+
+/// Remote storage of a sync test, a local directory used through the file backend
+#[allow(dead_code)] // Only used by the sync tests
+pub struct SyncEnv {
+    pub remote_dir: PathBuf,
+    pub url: String,
+}
+
+/// Set up an empty remote storage for a sync test
+#[allow(dead_code)] // Only used by the sync tests
+pub fn sync_env(test_name: &str) -> SyncEnv {
+    let remote_dir = std::env::current_dir()
+        .expect("Failed to get current directory")
+        .join(format!("tests/tmp/{test_name}_remote"));
+    let url = format!("file://{}", remote_dir.display());
+    let sync_env = SyncEnv { remote_dir, url };
+    sync_env.reset();
+    sync_env
+}
+
+#[allow(dead_code)] // Only used by the sync tests
+impl SyncEnv {
+    /// Run a clog command synced with the remote storage
+    pub fn run(&self, args: &[&str], db_path: &Path) -> String {
+        self.run_with_env(args, db_path, &[])
+    }
+
+    pub fn run_with_env(&self, args: &[&str], db_path: &Path, envs: &[(&str, &str)]) -> String {
+        let args = [&["run", "--"], args].concat();
+        let envs = [&[("CLOG_SYNC_URL", self.url.as_str())], envs].concat();
+        run_command_with_env(&args, db_path, &envs)
+    }
+
+    /// Content of the last upload, the newest version in the history of the remote storage
+    pub fn last_upload(&self) -> Vec<u8> {
+        let mut versions = std::fs::read_dir(self.remote_dir.join("history"))
+            .expect("Failed to read remote history")
+            .map(|entry| entry.expect("Failed to read remote version").path())
+            .collect::<Vec<_>>();
+        versions.sort();
+        let last_version = versions.last().expect("The remote history is empty");
+        std::fs::read(last_version).expect("Failed to read remote version")
+    }
+
+    pub fn reset(&self) {
+        if self.remote_dir.exists() {
+            std::fs::remove_dir_all(&self.remote_dir).expect("Failed to remove remote directory");
+        }
+    }
+}
+
 pub fn assert_snapshot(name: &str, output: &str) {
     let snapshot_path = get_snapshot_path(name);
     if Path::new(&snapshot_path).exists() {
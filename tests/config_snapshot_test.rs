@@ -0,0 +1,200 @@
+use std::path::Path;
+
+mod common;
+
+#[test]
+fn config_set_get_list_snapshot() {
+    let db_path = common::setup_test_db("config_set_get_list", 0);
+    let config_path = "tests/tmp/config_set_get_list.toml";
+    std::fs::write(
+        config_path,
+        "# Settings of the test\n[backup]\nkeep_last = 3\n",
+    )
+    .expect("Failed to write config file");
+    let config = format!("--config={config_path}");
+
+    let mut output = String::new();
+    output += &common::run_command(&["run", "--", "config", "path", &config], &db_path);
+    output += &common::run_command(
+        &[
+            "run",
+            "--",
+            "config",
+            "set",
+            "sync.url",
+            "file:///mnt/share/clog",
+            &config,
+        ],
+        &db_path,
+    );
+    output += &common::run_command(
+        &[
+            "run",
+            "--",
+            "config",
+            "set",
+            "sync.keep_versions",
+            "5",
+            &config,
+        ],
+        &db_path,
+    );
+    // Invalid settings are rejected without changing the file
+    output += &common::run_command(
+        &[
+            "run",
+            "--",
+            "config",
+            "set",
+            "sync.keep_versions",
+            "many",
+            &config,
+        ],
+        &db_path,
+    );
+    output += &common::run_command(
+        &["run", "--", "config", "get", "sync.url", &config],
+        &db_path,
+    );
+    // Environment variables override the file and `--set` overrides both
+    output += &common::run_command_with_env(
+        &[
+            "run",
+            "--",
+            "config",
+            "list",
+            "--set=sync.keep_versions=7",
+            &config,
+        ],
+        &db_path,
+        &[("CLOG_SYNC_PREFIX", "alice")],
+    );
+    output += &std::fs::read_to_string(config_path).expect("Failed to read config file");
+
+    common::assert_snapshot("config_set_get_list", &output);
+    common::reset_sqlite_db(&db_path);
+    if Path::new(config_path).exists() {
+        std::fs::remove_file(config_path).expect("Failed to remove config file");
+    }
+}
+
+#[test]
+fn profile_create_use_snapshot() {
+    let default_db_path = common::setup_test_db("profile_create_use", 0);
+    let personal_db_path = common::setup_test_db("profile_create_use", 1);
+    let config_path = "tests/tmp/profile_create_use.toml";
+    std::fs::write(
+        config_path,
+        format!("database = \"{}\"\n", default_db_path.display()),
+    )
+    .expect("Failed to write config file");
+    let config = format!("--config={config_path}");
+    // The database is selected by the profile instead of the environment
+    let run = |args: &[&str]| {
+        let args = [&["run", "--", &config], args].concat();
+        common::run_command_with_env(&args, &default_db_path, &[("CLOG_DATABASE_PATH", "")])
+    };
+
+    let mut output = String::new();
+    output += &run(&["project", "create", "-n", "work_project"]);
+    output += &run(&[
+        "profile",
+        "create",
+        "personal",
+        "--database",
+        &personal_db_path.display().to_string(),
+        "--sync-url",
+        "file:///mnt/share/personal",
+    ]);
+    output += &run(&["profile", "use", "personal"]);
+    output += &run(&["project", "create", "-n", "side_project"]);
+    output += &run(&["project", "list"]);
+    output += &run(&["--profile", "default", "project", "list"]);
+    // The absolute database paths are checked in the config file below
+    output += &run(&["profile", "list", "--columns=name,sync_url,active"]);
+    output += &std::fs::read_to_string(config_path).expect("Failed to read config file");
+
+    // Created profiles store the absolute path of their database
+    let current_dir = std::env::current_dir().expect("Failed to get current directory");
+    let output = output.replace(&format!("{}/", current_dir.display()), "");
+    common::assert_snapshot("profile_create_use", &output);
+    common::reset_sqlite_db(&default_db_path);
+    common::reset_sqlite_db(&personal_db_path);
+    if Path::new(config_path).exists() {
+        std::fs::remove_file(config_path).expect("Failed to remove config file");
+    }
+}
+
+#[test]
+fn profile_database_env_snapshot() {
+    let env_db_path = common::setup_test_db("profile_database_env", 0);
+    let profile_db_path = common::setup_test_db("profile_database_env", 1);
+    std::fs::write(
+        common::config_path(&env_db_path),
+        format!(
+            "[sync]\nkeyring = false\n\n[profiles.personal]\ndatabase = \"{}\"\n",
+            profile_db_path.display()
+        ),
+    )
+    .expect("Failed to write config file");
+
+    let mut output = String::new();
+    output += &common::run_command(
+        &["run", "--", "project", "create", "-n", "work_project"],
+        &env_db_path,
+    );
+    // The selected profile uses its own database instead of `CLOG_DATABASE_PATH`
+    output += &common::run_command(
+        &[
+            "run",
+            "--",
+            "--profile",
+            "personal",
+            "project",
+            "create",
+            "-n",
+            "side_project",
+        ],
+        &env_db_path,
+    );
+    output += &common::run_command_with_env(
+        &["run", "--", "project", "list"],
+        &env_db_path,
+        &[("CLOG_PROFILE", "personal")],
+    );
+    output += &common::run_command(&["run", "--", "project", "list"], &env_db_path);
+
+    common::assert_snapshot("profile_database_env", &output);
+    common::reset_sqlite_db(&env_db_path);
+    common::reset_sqlite_db(&profile_db_path);
+}
+
+#[test]
+fn config_invalid_setting_snapshot() {
+    let db_path = common::setup_test_db("config_invalid_setting", 0);
+    std::fs::write(
+        common::config_path(&db_path),
+        "[sync]\nkeyring = false\nstale_setting = 1\n",
+    )
+    .expect("Failed to write config file");
+
+    let mut output = String::new();
+    // Other commands fail, while the config commands work on the settings of the file
+    output += &common::run_command(&["run", "--", "project", "list"], &db_path);
+    output += &common::run_command(&["run", "--", "config", "list"], &db_path);
+    output += &common::run_command(&["run", "--", "config", "get", "sync.keyring"], &db_path);
+    output += &common::run_command(
+        &["run", "--", "config", "set", "sync.keep_versions", "5"],
+        &db_path,
+    );
+    // Settings which are invalid on their own are still rejected
+    output += &common::run_command(
+        &["run", "--", "config", "set", "sync.keep_versions", "many"],
+        &db_path,
+    );
+    output += &std::fs::read_to_string(common::config_path(&db_path))
+        .expect("Failed to read config file");
+
+    common::assert_snapshot("config_invalid_setting", &output);
+    common::reset_sqlite_db(&db_path);
+}
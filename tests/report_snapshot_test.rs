@@ -81,3 +81,265 @@ fn report_projects_yaml_snapshot() {
     common::assert_snapshot("report_projects_yaml_snapshot", &stdout);
     common::reset_sqlite_db(&db_path);
 }
+
+#[test]
+fn report_projects_iso_duration_snapshot() {
+    let db_path = common::setup_test_db("report_projects_iso_duration", 3);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "report",
+            "project",
+            "--duration-format",
+            "iso8601",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_projects_iso_duration_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_timesheet_human_snapshot() {
+    let db_path = common::setup_test_db("report_timesheet_human", 4);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &["run", "--", "report", "timesheet", "-w", "2025-W01"],
+        &db_path,
+    );
+    common::assert_snapshot("report_timesheet_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_timesheet_json_snapshot() {
+    let db_path = common::setup_test_db("report_timesheet_json", 5);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "json",
+            "report",
+            "timesheet",
+            "-w",
+            "2025-W01",
+            "-p",
+            "demo_project",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_timesheet_json_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_chart_human_snapshot() {
+    let db_path = common::setup_test_db("report_chart_human", 6);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "report",
+            "chart",
+            "--from",
+            "2025-01-27",
+            "--to",
+            "2025-02-16",
+            "--ascii",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_chart_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_chart_json_snapshot() {
+    let db_path = common::setup_test_db("report_chart_json", 7);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "json",
+            "report",
+            "chart",
+            "--from",
+            "2025-02-01",
+            "--to",
+            "2025-02-03",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_chart_json_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_projects_compare_human_snapshot() {
+    let db_path = common::setup_test_db("report_projects_compare_human", 8);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run", "--", "report", "project", "-w", "2025-W10", "-c", "previous",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_projects_compare_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_tasks_compare_json_snapshot() {
+    let db_path = common::setup_test_db("report_tasks_compare_json", 9);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "json",
+            "report",
+            "task",
+            "-p",
+            "demo_project",
+            "-d",
+            "2025-03-05",
+            "-c",
+            "2025-03-04",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_tasks_compare_json_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_projects_group_by_human_snapshot() {
+    let db_path = common::setup_test_db("report_projects_group_by_human", 10);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "report",
+            "project",
+            "-w",
+            "2025-W10",
+            "--group-by",
+            "project,day",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("report_projects_group_by_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_projects_rounding_human_snapshot() {
+    let db_path = common::setup_test_db("report_projects_rounding_human", 11);
+    setup(&db_path);
+    std::fs::write(
+        common::config_path(&db_path),
+        "[sync]\nkeyring = false\n\n\
+         [rounding]\nincrement_mins = 15\n\n\
+         [projects.demo_project.rounding]\nincrement_mins = 15\nmode = \"nearest\"\nscope = \"day\"\n",
+    )
+    .expect("Failed to write config file");
+    let time_entries = [
+        ("demo_task", "2025-03-05 09:00:00", 7),
+        ("demo_task_2", "2025-03-05 10:00:00", 0),
+        ("demo_task", "2025-03-06 09:00:00", 15),
+        ("another_task", "2025-03-05 09:00:00", 7),
+        ("another_task_2", "2025-03-05 10:00:00", 30),
+        ("another_task_2", "2025-03-06 09:00:00", 0),
+    ];
+    for (task_name, start_time, duration_mins) in time_entries {
+        common::insert_time_entry(&db_path, task_name, start_time, duration_mins);
+    }
+    let stdout = common::run_command(
+        &["run", "--", "report", "project", "-w", "2025-W10"],
+        &db_path,
+    );
+    common::assert_snapshot("report_projects_rounding_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn report_projects_group_by_client_tag_human_snapshot() {
+    let db_path = common::setup_test_db("report_projects_group_by_client_tag_human", 12);
+    setup(&db_path);
+    common::run_command(
+        &[
+            "run",
+            "--",
+            "project",
+            "edit",
+            "-n",
+            "demo_project",
+            "--new-client",
+            "acme",
+        ],
+        &db_path,
+    );
+    common::run_command(
+        &[
+            "run",
+            "--",
+            "task",
+            "edit",
+            "-n",
+            "demo_task",
+            "--new-tags",
+            "review,design",
+        ],
+        &db_path,
+    );
+    common::run_command(
+        &[
+            "run",
+            "--",
+            "task",
+            "edit",
+            "-n",
+            "another_task",
+            "--new-tags",
+            "review",
+        ],
+        &db_path,
+    );
+    let time_entries = [
+        ("demo_task", "2025-03-05 09:00:00", 30),
+        ("demo_task_2", "2025-03-05 10:00:00", 15),
+        ("another_task", "2025-03-06 09:00:00", 45),
+        ("another_task_2", "2025-03-06 10:00:00", 60),
+    ];
+    for (task_name, start_time, duration_mins) in time_entries {
+        common::insert_time_entry(&db_path, task_name, start_time, duration_mins);
+    }
+    // The subtotals count the time of a task with several tags only once
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "report",
+            "project",
+            "-w",
+            "2025-W10",
+            "--group-by",
+            "client,tag",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot(
+        "report_projects_group_by_client_tag_human_snapshot",
+        &stdout,
+    );
+    common::reset_sqlite_db(&db_path);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+#[test]
+fn status_human_snapshot() {
+    let db_path = common::setup_test_db("status_human", 0);
+    common::run_command(
+        &["run", "--", "project", "create", "-n", "demo_project"],
+        &db_path,
+    );
+    let stdout = common::run_command(&["run", "--", "status"], &db_path);
+    common::assert_snapshot("status_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn status_json_snapshot() {
+    let db_path = common::setup_test_db("status_json", 1);
+    let stdout = common::run_command(&["run", "--", "-o", "json", "status"], &db_path);
+    common::assert_snapshot("status_json_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn goal_json_snapshot() {
+    let db_path = common::setup_test_db("goal_json", 2);
+    std::fs::write(
+        common::config_path(&db_path),
+        "[sync]\nkeyring = false\n\n\
+         [goals]\ndaily_hours = 2\nweekly_hours = 10\n\n\
+         [projects.demo_project.goals]\ndaily_hours = 1\n",
+    )
+    .expect("Failed to write config file");
+    common::run_command(
+        &["run", "--", "project", "create", "-n", "demo_project"],
+        &db_path,
+    );
+    common::run_command(
+        &[
+            "run",
+            "--",
+            "task",
+            "create",
+            "-n",
+            "demo_task",
+            "-p",
+            "demo_project",
+        ],
+        &db_path,
+    );
+    // Started now, so it is tracked today and in the current week in any time zone
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    common::insert_time_entry(&db_path, "demo_task", &now, 90);
+    let stdout = common::run_command(&["run", "--", "-o", "json", "goal"], &db_path);
+    common::assert_snapshot("goal_json_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
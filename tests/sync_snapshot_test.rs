@@ -0,0 +1,537 @@
+use std::path::Path;
+
+mod common;
+
+#[test]
+fn sync_push_pull_file_backend_snapshot() {
+    let sync = common::sync_env("sync_push_pull_file_backend");
+
+    let local_db_path = common::setup_test_db("sync_push_pull_file_backend", 0);
+    sync.run(
+        &[
+            "project",
+            "create",
+            "-n",
+            "synced_project",
+            "-d",
+            "A synced project",
+        ],
+        &local_db_path,
+    );
+    let push_output = sync.run(&["sync", "push"], &local_db_path);
+
+    let other_db_path = common::setup_test_db("sync_push_pull_file_backend", 1);
+    let pull_output = sync.run(&["sync", "pull"], &other_db_path);
+    let list_output = sync.run(&["project", "list"], &other_db_path);
+
+    common::assert_snapshot(
+        "sync_push_pull_file_backend",
+        &format!("{push_output}{pull_output}{list_output}"),
+    );
+    common::reset_sqlite_db(&local_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_push_conflict_file_backend_snapshot() {
+    let sync = common::sync_env("sync_push_conflict_file_backend");
+    let run = |args: &[&str], db_path: &Path| sync.run(args, db_path);
+
+    let first_db_path = common::setup_test_db("sync_push_conflict_file_backend", 0);
+    let second_db_path = common::setup_test_db("sync_push_conflict_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "first_project"],
+        &first_db_path,
+    );
+    output += &run(&["sync", "push"], &first_db_path);
+    output += &run(&["sync", "pull"], &second_db_path);
+    output += &run(
+        &["project", "create", "-n", "second_project"],
+        &second_db_path,
+    );
+    output += &run(&["sync", "push"], &second_db_path);
+
+    // The remote database changed since the first database was synced, so the push is refused
+    output += &run(
+        &["project", "create", "-n", "third_project"],
+        &first_db_path,
+    );
+    output += &run(&["sync", "push"], &first_db_path);
+    output += &run(&["sync", "pull"], &first_db_path);
+    output += &run(&["project", "list"], &first_db_path);
+
+    // Forcing the push overwrites the remote changes
+    output += &run(&["sync", "push", "--force"], &first_db_path);
+    output += &run(&["sync", "pull"], &second_db_path);
+    output += &run(&["project", "list"], &second_db_path);
+
+    common::assert_snapshot("sync_push_conflict_file_backend", &output);
+    common::reset_sqlite_db(&first_db_path);
+    common::reset_sqlite_db(&second_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_merge_file_backend_snapshot() {
+    let sync = common::sync_env("sync_merge_file_backend");
+    let run = |args: &[&str], db_path: &Path| sync.run(args, db_path);
+
+    let first_db_path = common::setup_test_db("sync_merge_file_backend", 0);
+    let second_db_path = common::setup_test_db("sync_merge_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "shared_project"],
+        &first_db_path,
+    );
+    output += &run(&["sync"], &first_db_path);
+    output += &run(&["sync"], &second_db_path);
+
+    // Both databases change different rows, which are merged without conflicts
+    output += &run(
+        &["task", "create", "-n", "first_task", "-p", "shared_project"],
+        &first_db_path,
+    );
+    output += &run(
+        &["project", "edit", "-n", "shared_project", "-d", "Edited"],
+        &second_db_path,
+    );
+    output += &run(
+        &["project", "create", "-n", "second_project"],
+        &second_db_path,
+    );
+    output += &run(&["sync"], &first_db_path);
+    output += &run(&["sync"], &second_db_path);
+    output += &run(&["sync"], &first_db_path);
+    output += &run(&["project", "list"], &first_db_path);
+    output += &run(&["project", "list"], &second_db_path);
+
+    common::assert_snapshot("sync_merge_file_backend", &output);
+    common::reset_sqlite_db(&first_db_path);
+    common::reset_sqlite_db(&second_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_pull_corrupted_remote_file_backend_snapshot() {
+    let sync = common::sync_env("sync_pull_corrupted_remote_file_backend");
+    let run = |args: &[&str], db_path: &Path| sync.run(args, db_path);
+
+    let db_path = common::setup_test_db("sync_pull_corrupted_remote_file_backend", 0);
+    let mut output = String::new();
+    output += &run(&["project", "create", "-n", "local_project"], &db_path);
+    output += &run(&["sync", "push"], &db_path);
+
+    // The downloaded database is validated before it replaces the local database
+    std::fs::write(sync.remote_dir.join("clog-db.sqlite"), "not a database")
+        .expect("Failed to corrupt remote database");
+    output += &run(&["sync", "pull", "--force"], &db_path);
+    output += &run(&["project", "list"], &db_path);
+
+    common::assert_snapshot("sync_pull_corrupted_remote_file_backend", &output);
+    common::reset_sqlite_db(&db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_encrypted_file_backend_snapshot() {
+    let sync = common::sync_env("sync_encrypted_file_backend");
+    let run = |args: &[&str], db_path: &Path, passphrase: &str| {
+        sync.run_with_env(args, db_path, &[("CLOG_SYNC_PASSPHRASE", passphrase)])
+    };
+
+    let local_db_path = common::setup_test_db("sync_encrypted_file_backend", 0);
+    let other_db_path = common::setup_test_db("sync_encrypted_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "confidential_client"],
+        &local_db_path,
+        "correct horse",
+    );
+    output += &run(&["sync", "push"], &local_db_path, "correct horse");
+    let remote_content = sync.last_upload();
+    assert!(
+        !remote_content
+            .windows(b"confidential_client".len())
+            .any(|window| window == b"confidential_client"),
+        "The remote database is not encrypted"
+    );
+    // The wrong passphrase fails with an error and leaves the local database unchanged
+    output += &run(&["sync", "pull"], &other_db_path, "battery staple");
+    output += &run(&["project", "list"], &other_db_path, "battery staple");
+    output += &run(&["sync", "pull"], &other_db_path, "correct horse");
+    output += &run(&["project", "list"], &other_db_path, "correct horse");
+
+    common::assert_snapshot("sync_encrypted_file_backend", &output);
+    common::reset_sqlite_db(&local_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_unencrypted_remote_with_key_file_backend_snapshot() {
+    let sync = common::sync_env("sync_unencrypted_remote_with_key_file_backend");
+    let run = |args: &[&str], db_path: &Path, passphrase: &str| {
+        sync.run_with_env(args, db_path, &[("CLOG_SYNC_PASSPHRASE", passphrase)])
+    };
+
+    let local_db_path = common::setup_test_db("sync_unencrypted_remote_with_key_file_backend", 0);
+    let other_db_path = common::setup_test_db("sync_unencrypted_remote_with_key_file_backend", 1);
+    let mut output = String::new();
+    output += &run(&["project", "create", "-n", "planted"], &local_db_path, "");
+    output += &run(&["sync", "push"], &local_db_path, "");
+    // An unencrypted remote database is neither pulled nor merged once a key is configured
+    output += &run(
+        &["project", "create", "-n", "confidential_client"],
+        &other_db_path,
+        "correct horse",
+    );
+    output += &run(
+        &["sync", "pull", "--force"],
+        &other_db_path,
+        "correct horse",
+    );
+    output += &run(&["sync"], &other_db_path, "correct horse");
+    output += &run(&["project", "list"], &other_db_path, "correct horse");
+
+    common::assert_snapshot("sync_unencrypted_remote_with_key_file_backend", &output);
+    common::reset_sqlite_db(&local_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_uncompressed_remote_file_backend_snapshot() {
+    let sync = common::sync_env("sync_uncompressed_remote_file_backend");
+
+    // Older versions of clog uploaded the plain database file
+    let legacy_db_path = common::setup_test_db("sync_uncompressed_remote_file_backend", 0);
+    let mut output = common::run_command(
+        &["run", "--", "project", "create", "-n", "legacy_project"],
+        &legacy_db_path,
+    );
+    std::fs::create_dir_all(&sync.remote_dir).expect("Failed to create remote directory");
+    std::fs::copy(&legacy_db_path, sync.remote_dir.join("clog-db.sqlite"))
+        .expect("Failed to copy database to remote directory");
+
+    let other_db_path = common::setup_test_db("sync_uncompressed_remote_file_backend", 1);
+    output += &sync.run(&["sync", "pull"], &other_db_path);
+    output += &sync.run(&["project", "list"], &other_db_path);
+    output += &sync.run(&["sync", "push"], &other_db_path);
+    let remote_content = sync.last_upload();
+    assert!(
+        remote_content.starts_with(b"CLOGDB"),
+        "The remote database is not compressed"
+    );
+
+    common::assert_snapshot("sync_uncompressed_remote_file_backend", &output);
+    common::reset_sqlite_db(&legacy_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_status_dry_run_file_backend_snapshot() {
+    let sync = common::sync_env("sync_status_dry_run_file_backend");
+    let run = |args: &[&str], db_path: &Path| sync.run(args, db_path);
+    // Sizes, times and ETags differ between runs
+    let status = ["sync", "status", "--columns", "database,changed"];
+
+    let local_db_path = common::setup_test_db("sync_status_dry_run_file_backend", 0);
+    let other_db_path = common::setup_test_db("sync_status_dry_run_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "first_project"],
+        &local_db_path,
+    );
+    output += &run(&status, &local_db_path);
+    output += &run(&["sync", "push"], &local_db_path);
+    output += &run(&status, &local_db_path);
+    output += &run(
+        &["project", "create", "-n", "second_project"],
+        &local_db_path,
+    );
+    output += &run(&status, &local_db_path);
+    output += &run(&["sync", "push", "--dry-run"], &local_db_path);
+    output += &run(&["sync", "pull", "--dry-run"], &other_db_path);
+    // Nothing was uploaded or replaced by the dry runs
+    output += &run(&status, &local_db_path);
+    output += &run(&["project", "list"], &other_db_path);
+
+    // The remote directory is an absolute path which depends on the checkout
+    let output = output.replace(&sync.url, "file:///remote");
+    common::assert_snapshot("sync_status_dry_run_file_backend", &output);
+    common::reset_sqlite_db(&local_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_history_file_backend_snapshot() {
+    let sync = common::sync_env("sync_history_file_backend");
+    let run = |args: &[&str], db_path: &Path| sync.run(args, db_path);
+
+    let local_db_path = common::setup_test_db("sync_history_file_backend", 0);
+    let other_db_path = common::setup_test_db("sync_history_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "first_project"],
+        &local_db_path,
+    );
+    output += &run(&["sync", "push"], &local_db_path);
+    output += &run(
+        &["project", "create", "-n", "second_project"],
+        &local_db_path,
+    );
+    output += &run(&["sync", "push"], &local_db_path);
+
+    // Versions are named after the time of the upload, so only their number is compared
+    let history: serde_json::Value =
+        serde_json::from_str(&run(&["-o", "json", "sync", "history"], &local_db_path))
+            .expect("Failed to parse sync history");
+    let versions = history["table_rows"]
+        .as_array()
+        .expect("Sync history has no versions");
+    assert_eq!(versions.len(), 2);
+    // The latest remote database refers to the newest version instead of uploading it again
+    let latest = std::fs::read(sync.remote_dir.join("clog-db.sqlite"))
+        .expect("Failed to read remote database");
+    assert!(latest.len() < sync.last_upload().len());
+    let first_version = versions[1]["version"].as_str().expect("Version has no id");
+    output += &run(
+        &["sync", "pull", "--version", first_version],
+        &other_db_path,
+    )
+    .replace(first_version, "<version>");
+    output += &run(&["project", "list"], &other_db_path);
+
+    common::assert_snapshot("sync_history_file_backend", &output);
+    common::reset_sqlite_db(&local_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_prefix_import_file_backend_snapshot() {
+    let sync = common::sync_env("sync_prefix_import_file_backend");
+    let run = |args: &[&str], db_path: &Path, prefix: &str| {
+        sync.run_with_env(args, db_path, &[("CLOG_SYNC_PREFIX", prefix)])
+    };
+
+    let alice_db_path = common::setup_test_db("sync_prefix_import_file_backend", 0);
+    let bob_db_path = common::setup_test_db("sync_prefix_import_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "alice_project"],
+        &alice_db_path,
+        "alice",
+    );
+    output += &run(&["sync", "push"], &alice_db_path, "alice");
+    output += &run(
+        &["project", "create", "-n", "bob_project"],
+        &bob_db_path,
+        "bob/laptop",
+    );
+    output += &run(&["sync", "push"], &bob_db_path, "bob/laptop");
+    assert!(sync.remote_dir.join("alice/clog-db.sqlite").exists());
+    assert!(sync.remote_dir.join("bob/laptop/clog-db.sqlite").exists());
+
+    // The imported database is kept apart from the local one
+    output += &run(
+        &["sync", "pull", "--from", "alice"],
+        &bob_db_path,
+        "bob/laptop",
+    );
+    assert!(
+        Path::new(&format!("{}.team/alice.sqlite", bob_db_path.display())).exists(),
+        "The database of alice was not imported"
+    );
+    output += &run(&["project", "list"], &bob_db_path, "bob/laptop");
+
+    common::assert_snapshot("sync_prefix_import_file_backend", &output);
+    common::reset_sqlite_db(&alice_db_path);
+    common::reset_sqlite_db(&bob_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_team_report_file_backend_snapshot() {
+    let sync = common::sync_env("sync_team_report_file_backend");
+    let run = |args: &[&str], db_path: &Path, prefix: &str| {
+        sync.run_with_env(args, db_path, &[("CLOG_SYNC_PREFIX", prefix)])
+    };
+
+    let alice_db_path = common::setup_test_db("sync_team_report_file_backend", 0);
+    let bob_db_path = common::setup_test_db("sync_team_report_file_backend", 1);
+    for (db_path, prefix) in [(&alice_db_path, "alice"), (&bob_db_path, "bob")] {
+        run(&["project", "create", "-n", "shared"], db_path, prefix);
+        run(
+            &["task", "create", "-n", "review", "-p", "shared"],
+            db_path,
+            prefix,
+        );
+        run(&["task", "start", "-n", "review"], db_path, prefix);
+    }
+    run(
+        &["project", "create", "-n", "internal"],
+        &alice_db_path,
+        "alice",
+    );
+    run(
+        &["task", "create", "-n", "planning", "-p", "internal"],
+        &alice_db_path,
+        "alice",
+    );
+    run(
+        &["task", "start", "-n", "planning"],
+        &alice_db_path,
+        "alice",
+    );
+    run(&["sync", "push"], &alice_db_path, "alice");
+    run(&["sync", "pull", "--from", "alice"], &bob_db_path, "bob");
+
+    // Durations depend on the time the tasks were started, so they are left out
+    let columns = "--columns=group,dimension,tasks,time_entries";
+    let mut output = String::new();
+    output += &run(&["team", "report", columns], &bob_db_path, "bob");
+    output += &run(
+        &["team", "report", "--by", "project", columns],
+        &bob_db_path,
+        "bob",
+    );
+    // A file named like an imported teammate is rejected instead of adding up their time
+    let files_dir = Path::new("tests/tmp/sync_team_report_file_backend_files");
+    std::fs::create_dir_all(files_dir).expect("Failed to create files directory");
+    let alice_file = files_dir.join("alice.sqlite");
+    std::fs::copy(&alice_db_path, &alice_file).expect("Failed to copy database");
+    output += &run(
+        &[
+            "team",
+            "report",
+            "--file",
+            &alice_file.display().to_string(),
+            columns,
+        ],
+        &bob_db_path,
+        "bob",
+    );
+    output += &run(&["project", "list"], &bob_db_path, "bob");
+
+    common::assert_snapshot("sync_team_report_file_backend", &output);
+    std::fs::remove_dir_all(files_dir).expect("Failed to remove files directory");
+    common::reset_sqlite_db(&alice_db_path);
+    common::reset_sqlite_db(&bob_db_path);
+    sync.reset();
+}
+
+#[test]
+fn sync_credential_helper_file_backend_snapshot() {
+    let sync = common::sync_env("sync_credential_helper_file_backend");
+    let helper_path = "tests/tmp/sync_credential_helper_file_backend_helper.sh";
+    std::fs::write(
+        helper_path,
+        "[ \"$1\" = get ] && [ \"$2\" = passphrase ] && echo 'correct horse'\nexit 0\n",
+    )
+    .expect("Failed to write credential helper");
+    let helper = format!("--set=sync.credential_helper=\"sh {helper_path}\"");
+    // The keyring of the machine running the tests is left alone
+    let run = |args: &[&str], db_path: &Path, envs: &[(&str, &str)]| {
+        let args = [&["--set=sync.keyring=false"], args].concat();
+        sync.run_with_env(&args, db_path, envs)
+    };
+
+    let local_db_path = common::setup_test_db("sync_credential_helper_file_backend", 0);
+    let other_db_path = common::setup_test_db("sync_credential_helper_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "confidential_client"],
+        &local_db_path,
+        &[],
+    );
+    output += &run(
+        &["sync", "credential", "list", &helper],
+        &local_db_path,
+        &[],
+    );
+    output += &run(&["sync", "push", &helper], &local_db_path, &[]);
+    let remote_content = sync.last_upload();
+    assert!(
+        remote_content.starts_with(b"CLOGENC"),
+        "The remote database is not encrypted"
+    );
+    // The environment is the fallback without a credential helper
+    output += &run(
+        &["sync", "pull"],
+        &other_db_path,
+        &[("CLOG_SYNC_PASSPHRASE", "correct horse")],
+    );
+    output += &run(&["project", "list"], &other_db_path, &[]);
+
+    common::assert_snapshot("sync_credential_helper_file_backend", &output);
+    common::reset_sqlite_db(&local_db_path);
+    common::reset_sqlite_db(&other_db_path);
+    std::fs::remove_file(helper_path).expect("Failed to remove credential helper");
+    sync.reset();
+}
+
+#[test]
+fn sync_auto_file_backend_snapshot() {
+    let sync = common::sync_env("sync_auto_file_backend");
+    // Without an interval every command checks the remote storage
+    let run = |args: &[&str], db_path: &Path| {
+        let args = [
+            &[
+                "--set=sync.auto.enabled=true",
+                "--set=sync.auto.interval_secs=0",
+            ],
+            args,
+        ]
+        .concat();
+        sync.run(&args, db_path)
+    };
+
+    let laptop_db_path = common::setup_test_db("sync_auto_file_backend", 0);
+    let desktop_db_path = common::setup_test_db("sync_auto_file_backend", 1);
+    let mut output = String::new();
+    output += &run(
+        &["project", "create", "-n", "laptop_project"],
+        &laptop_db_path,
+    );
+    output += &run(&["sync", "pull"], &desktop_db_path);
+    // The laptop pulls the project of the desktop before creating its task
+    output += &run(
+        &["project", "create", "-n", "desktop_project"],
+        &desktop_db_path,
+    );
+    output += &run(
+        &["task", "create", "-n", "review", "-p", "desktop_project"],
+        &laptop_db_path,
+    );
+    output += &run(&["project", "list"], &desktop_db_path);
+    // Changes made without the automatic sync are pushed by the next changing command
+    output += &run(
+        &["project", "create", "-n", "offline_project", "--no-sync"],
+        &laptop_db_path,
+    );
+    output += &run(
+        &["sync", "status", "--columns=database,changed"],
+        &laptop_db_path,
+    );
+    output += &run(
+        &["task", "create", "-n", "notes", "-p", "offline_project"],
+        &laptop_db_path,
+    );
+    output += &run(
+        &["task", "create", "-n", "planning", "-p", "offline_project"],
+        &desktop_db_path,
+    );
+    output += &run(&["project", "list"], &desktop_db_path);
+
+    // The remote directory is an absolute path which depends on the checkout
+    let output = output.replace(&sync.url, "file:///remote");
+    common::assert_snapshot("sync_auto_file_backend", &output);
+    common::reset_sqlite_db(&laptop_db_path);
+    common::reset_sqlite_db(&desktop_db_path);
+    sync.reset();
+}
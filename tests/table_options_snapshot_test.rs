@@ -0,0 +1,82 @@
+use std::path::Path;
+
+mod common;
+
+fn setup(db_path: &Path) {
+    let projects = [
+        ("alpha", "The first project"),
+        ("charlie", "The third project"),
+        ("bravo", "The second project"),
+    ];
+    for (name, description) in projects.into_iter() {
+        common::run_command(
+            &[
+                "run",
+                "--",
+                "project",
+                "create",
+                "-n",
+                name,
+                "-d",
+                description,
+            ],
+            db_path,
+        );
+    }
+}
+
+#[test]
+fn list_projects_columns_human_snapshot() {
+    let db_path = common::setup_test_db("list_projects_columns_human", 0);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &["run", "--", "project", "list", "--columns", "name,id"],
+        &db_path,
+    );
+    common::assert_snapshot("list_projects_columns_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn list_projects_sorted_human_snapshot() {
+    let db_path = common::setup_test_db("list_projects_sorted_human", 1);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "project",
+            "list",
+            "--sort-by",
+            "name:desc",
+            "--limit",
+            "2",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("list_projects_sorted_human_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn list_projects_columns_json_snapshot() {
+    let db_path = common::setup_test_db("list_projects_columns_json", 2);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "json",
+            "project",
+            "list",
+            "--columns",
+            "id,name",
+            "--sort-by",
+            "name",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("list_projects_columns_json_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+mod common;
+
+fn setup(db_path: &Path) {
+    let projects = [
+        ("demo_project", "A demo project"),
+        ("another_project", "Another project"),
+    ];
+    for (name, description) in projects.into_iter() {
+        common::run_command(
+            &[
+                "run",
+                "--",
+                "project",
+                "create",
+                "-n",
+                name,
+                "-d",
+                description,
+            ],
+            db_path,
+        );
+    }
+}
+
+#[test]
+fn list_projects_template_snapshot() {
+    let db_path = common::setup_test_db("list_projects_template", 0);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "template",
+            "project",
+            "list",
+            "--template",
+            "{id}/{name|upper}: {tasks} tasks {{done}}",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("list_projects_template_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn list_projects_template_file_snapshot() {
+    let db_path = common::setup_test_db("list_projects_template_file", 1);
+    setup(&db_path);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "template",
+            "project",
+            "list",
+            "--template-file",
+            "tests/templates/project_summary.tmpl",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("list_projects_template_file_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}
+
+#[test]
+fn create_project_template_snapshot() {
+    let db_path = common::setup_test_db("create_project_template", 2);
+    let stdout = common::run_command(
+        &[
+            "run",
+            "--",
+            "-o",
+            "template",
+            "project",
+            "create",
+            "-n",
+            "demo_project",
+            "--template",
+            "> {message}",
+        ],
+        &db_path,
+    );
+    common::assert_snapshot("create_project_template_snapshot", &stdout);
+    common::reset_sqlite_db(&db_path);
+}